@@ -28,8 +28,8 @@ async fn get_route() -> Result<()> {
     let mut route_service = RouteService::new_with_api_key(api_key);
 
     let masks = vec!["routes.duration", "routes.distanceMeters"];
-    let origin = WayPoint::new_from_location(Location::new(37.419734, -122.0827784, None), None)?;
-    let destination = WayPoint::new_from_location(Location::new(35.419734, -100.0827784, None), None)?;
+    let origin = WayPoint::new_from_location(Location::new(37.419734, -122.08278, None), None)?;
+    let destination = WayPoint::new_from_location(Location::new(35.419734, -100.08278, None), None)?;
 
     let mut mid_waypoint_option: HashMap<String, Value> = HashMap::new();
     mid_waypoint_option.insert("via".to_string(), true.into());
@@ -69,8 +69,8 @@ async fn get_route_matrix() -> Result<()> {
     let mut route_service = RouteService::new_with_api_key(api_key);
 
     let masks = vec!["originIndex", "destinationIndex", "status", "condition", "distanceMeters", "duration"];
-    let origin = WayPoint::new_from_location(Location::new(37.419734, -122.0827784, None), None)?;
-    let destination = WayPoint::new_from_location(Location::new(35.419734, -100.0827784, None), None)?;
+    let origin = WayPoint::new_from_location(Location::new(37.419734, -122.08278, None), None)?;
+    let destination = WayPoint::new_from_location(Location::new(35.419734, -100.08278, None), None)?;
 
     let mut route_option: HashMap<String, Value> = HashMap::new();
     // let mid_waypoint = WayPoint::new_from_place_id("ChIJgUbEo8cfqokR5lP9_Wh_DaM", Some(mid_waypoint_option))?;
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use lru::LruCache;
+use reqwest::Client;
+use tokio::sync::{Mutex as AsyncMutex, Semaphore};
+
+use super::service_account::ServiceAccountCredentials;
+
+static DEFAULT_MAX_CONCURRENCY_PER_SUBJECT: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    subject: String,
+    scopes: Vec<String>,
+}
+
+impl PoolKey {
+    fn new(subject: &str, scopes: &[&str]) -> Self {
+        let mut scopes: Vec<String> = scopes.iter().map(|s| s.to_string()).collect();
+        scopes.sort();
+        scopes.dedup();
+        Self { subject: subject.to_owned(), scopes }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PoolCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    token_requests: AtomicU64,
+}
+
+/// An entry of a map of locks, removed from the map when the last caller using it is done,
+/// so subjects whose exchange failed or that are no longer used do not keep their locks.
+struct SharedLock<'a, K: Eq + Hash, V> {
+    locks: &'a Mutex<HashMap<K, Arc<V>>>,
+    key: K,
+    lock: Arc<V>,
+}
+
+impl<'a, K: Eq + Hash + Clone, V> SharedLock<'a, K, V> {
+    fn get(locks: &'a Mutex<HashMap<K, Arc<V>>>, key: &K, create: impl FnOnce() -> V) -> Self {
+        let lock = locks.lock().unwrap_or_else(|e| e.into_inner()).entry(key.clone()).or_insert_with(|| Arc::new(create())).clone();
+        Self { locks, key: key.clone(), lock }
+    }
+}
+
+impl<K: Eq + Hash, V> Drop for SharedLock<'_, K, V> {
+    fn drop(&mut self) {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        // held by the map and this caller only
+        if Arc::strong_count(&self.lock) == 2 {
+            locks.remove(&self.key);
+        }
+    }
+}
+
+/// A snapshot of the counters of a `DelegatedCredentialsPool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelegatedCredentialsPoolMetrics {
+    /// Number of token lookups served from a cached, unexpired token.
+    pub hits: u64,
+    /// Number of token lookups that required a token exchange.
+    pub misses: u64,
+    /// Number of cached credentials evicted to make room for new subjects and scopes.
+    pub evictions: u64,
+    /// Number of token exchanges that completed successfully.
+    pub token_requests: u64,
+    /// Number of credentials currently cached.
+    pub cached_entries: usize,
+}
+
+/// A pool of delegated `ServiceAccountCredentials` keyed by subject and scopes.
+///
+/// Each (subject, scopes) pair keeps its own access token, so impersonating the same user again reuses the cached token
+/// instead of requesting a new one. The least recently used entries are evicted once `capacity` is reached.
+/// Cloning the pool is cheap, and clones share the same cache.
+#[derive(Debug, Clone)]
+pub struct DelegatedCredentialsPool {
+    credentials: ServiceAccountCredentials,
+    cache: Arc<Mutex<LruCache<PoolKey, ServiceAccountCredentials>>>,
+    semaphores: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    exchanges: Arc<Mutex<HashMap<PoolKey, Arc<AsyncMutex<()>>>>>,
+    max_concurrency_per_subject: usize,
+    counters: Arc<PoolCounters>,
+}
+
+impl DelegatedCredentialsPool {
+    /// Create `DelegatedCredentialsPool`.
+    ///
+    /// * `credentials` -  `ServiceAccountCredentials` authorized for [Domain-wide delegation](https://support.google.com/a/answer/162106).
+    /// * `capacity` -  Maximum number of (subject, scopes) pairs to keep tokens for.
+    pub fn new(credentials: ServiceAccountCredentials, capacity: usize) -> Result<Self> {
+        let capacity = NonZeroUsize::new(capacity).ok_or(anyhow!("Pool capacity must be greater than 0!"))?;
+        Ok(Self {
+            credentials,
+            cache: Arc::new(Mutex::new(LruCache::new(capacity))),
+            semaphores: Arc::new(Mutex::new(HashMap::new())),
+            exchanges: Arc::new(Mutex::new(HashMap::new())),
+            max_concurrency_per_subject: DEFAULT_MAX_CONCURRENCY_PER_SUBJECT,
+            counters: Arc::new(PoolCounters::default()),
+        })
+    }
+
+    /// Limit the number of concurrent token exchanges for a single subject, across its scope sets. Defaults to 4.
+    ///
+    /// * `max_concurrency` -  Maximum number of concurrent token exchanges per subject.
+    pub fn with_max_concurrency_per_subject(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency_per_subject = max_concurrency.max(1);
+        self
+    }
+
+    /// Use the given HTTP client for token requests.
+    ///
+    /// * `client` -  `reqwest::Client` to use. See `ClientConfig` to build a configured client.
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.credentials = self.credentials.with_http_client(client);
+        self
+    }
+
+    /// Create `DelegatedCredentials` that get their tokens from this pool for the given subject.
+    ///
+    /// * `subject` -  The email address of the user for which the application is requesting delegated access.
+    pub fn delegate(&self, subject: &str) -> DelegatedCredentials {
+        DelegatedCredentials { pool: self.clone(), subject: subject.to_owned(), scopes: vec![] }
+    }
+
+    /// Get an access token for the subject and scopes, reusing a cached token if one is still valid.
+    ///
+    /// Concurrent callers missing the cache for the same subject and scopes share a single token exchange.
+    ///
+    /// * `subject` -  The email address of the user for which the application is requesting delegated access.
+    /// * `scopes` -  Scopes that your application needs access to. [OAuth 2.0 Scopes](https://developers.google.com/identity/protocols/oauth2/scopes)
+    pub async fn get_access_token(&self, subject: &str, scopes: Vec<&str>) -> Result<String> {
+        let key = PoolKey::new(subject, &scopes);
+        if let Some(access_token) = self.cached_token(&key).await? {
+            return Ok(access_token);
+        }
+
+        let semaphore = SharedLock::get(&self.semaphores, &subject.to_owned(), || Semaphore::new(self.max_concurrency_per_subject));
+        let _permit = semaphore.lock.acquire().await?;
+        let exchange = SharedLock::get(&self.exchanges, &key, || AsyncMutex::new(()));
+        let _exchange = exchange.lock.lock().await;
+
+        // another caller may have exchanged the token while this one was waiting
+        if let Some(access_token) = self.cached_token(&key).await? {
+            return Ok(access_token);
+        }
+
+        self.counters.misses.fetch_add(1, Ordering::Relaxed);
+        let cached = self.cache.lock().map_err(|_| anyhow!("Pool lock poisoned!"))?.peek(&key).cloned();
+        let mut credentials = cached.unwrap_or_else(|| self.credentials.with_scopes(scopes).with_subject(subject));
+        let access_token = credentials.get_access_token().await?;
+        self.counters.token_requests.fetch_add(1, Ordering::Relaxed);
+        self.insert(key, credentials)?;
+
+        Ok(access_token)
+    }
+
+    /// Current counters of the pool.
+    pub fn metrics(&self) -> DelegatedCredentialsPoolMetrics {
+        let cached_entries = self.cache.lock().map(|cache| cache.len()).unwrap_or_default();
+        DelegatedCredentialsPoolMetrics {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+            token_requests: self.counters.token_requests.load(Ordering::Relaxed),
+            cached_entries,
+        }
+    }
+
+    /// Unexpired token cached for the key, counting the lookup as a hit.
+    async fn cached_token(&self, key: &PoolKey) -> Result<Option<String>> {
+        let cached = self.cache.lock().map_err(|_| anyhow!("Pool lock poisoned!"))?.get(key).cloned();
+        let Some(mut credentials) = cached.filter(|credentials| credentials.has_valid_token()) else {
+            return Ok(None);
+        };
+        self.counters.hits.fetch_add(1, Ordering::Relaxed);
+        // a valid token is returned without a request
+        Ok(Some(credentials.get_access_token().await?))
+    }
+
+    fn insert(&self, key: PoolKey, credentials: ServiceAccountCredentials) -> Result<()> {
+        let mut cache = self.cache.lock().map_err(|_| anyhow!("Pool lock poisoned!"))?;
+        if let Some((evicted_key, _)) = cache.push(key.clone(), credentials) {
+            if evicted_key != key {
+                self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Delegated credentials for a single subject, backed by a `DelegatedCredentialsPool`.
+#[derive(Debug, Clone)]
+pub struct DelegatedCredentials {
+    pool: DelegatedCredentialsPool,
+    subject: String,
+    scopes: Vec<String>,
+}
+
+impl DelegatedCredentials {
+    /// Add scopes to request the access token for.
+    ///
+    /// * `scopes` -  Scopes that your application needs access to. [OAuth 2.0 Scopes](https://developers.google.com/identity/protocols/oauth2/scopes)
+    pub fn with_scopes(&self, scopes: Vec<&str>) -> Self {
+        let mut scoped_credentials = self.clone();
+        scoped_credentials.scopes = scopes.into_iter().map(|s| s.to_owned()).collect();
+        scoped_credentials
+    }
+
+    /// Use the given HTTP client for the token requests of this subject.
+    ///
+    /// * `client` -  `reqwest::Client` to use. See `ClientConfig` to build a configured client.
+    pub fn with_http_client(&self, client: Client) -> Self {
+        let mut credentials = self.clone();
+        credentials.pool = self.pool.clone().with_http_client(client);
+        credentials
+    }
+
+    /// Use the given HTTP client for token requests, unless the pool has one set with `with_http_client`.
+    #[cfg(any(feature = "translate", feature = "routes"))]
+    pub(crate) fn with_default_http_client(&self, client: Client) -> Self {
+        let mut credentials = self.clone();
+        credentials.pool.credentials = self.pool.credentials.with_default_http_client(client);
+        credentials
+    }
+
+    /// Service account and subject the access tokens are issued for, to tell callers apart.
+    #[cfg(any(feature = "translate", feature = "routes"))]
+    pub(crate) fn identity(&self) -> String {
+        format!("{}/{}", self.pool.credentials.client_email(), self.subject)
+    }
+
+    /// Get an access token for the subject from the pool.
+    pub async fn get_access_token(&self) -> Result<String> {
+        self.pool.get_access_token(&self.subject, self.scopes.iter().map(|s| s.as_str()).collect()).await
+    }
+}
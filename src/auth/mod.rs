@@ -1,3 +1,6 @@
 
 pub mod service_account;
 pub mod auth_error;
+pub mod watched_credentials;
+pub mod delegated_credentials_pool;
+pub mod token_info;
@@ -1,15 +1,19 @@
 use std::fs;
 use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Instant;
 
-use anyhow::{bail, Ok, Result};
+use anyhow::{anyhow, bail, Ok, Result};
 use chrono::{Local, Duration};
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use reqwest::header::{HeaderValue, CONTENT_TYPE, HeaderMap};
 use reqwest::Client;
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
+use tracing::Span;
 
 use super::auth_error::AuthErrorResponse;
+use super::token_info::{self, TokenInfo};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServiceAccountCredentials {
@@ -30,7 +34,9 @@ pub struct ServiceAccountCredentials {
     #[serde(skip_serializing_if = "Option::is_none")]
     scopes: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    sub: Option<String>
+    sub: Option<String>,
+    #[serde(skip)]
+    client: Option<Client>,
 }
 
 
@@ -56,6 +62,71 @@ impl ServiceAccountCredentials {
         Ok(serde_json::from_str::<ServiceAccountCredentials>(&credentials_json)?)
     }
 
+    /// Revoke the cached access token, if any. The next call to `get_access_token` requests a new token.
+    /// If revocation fails, the token stays cached, since it is still valid.
+    pub async fn revoke(&mut self) -> Result<()> {
+        self.revoke_at(token_info::REVOKE_URL).await
+    }
+
+    async fn revoke_at(&mut self, url: &str) -> Result<()> {
+        if let Some(token) = &self.token {
+            token_info::revoke_at(&self.http_client(), url, &token.access_token).await?;
+            self.token = None;
+        }
+        Ok(())
+    }
+
+    /// Get the scopes, expiry, audience and email of the current access token, requesting one if needed.
+    /// Useful for debugging insufficient scopes errors.
+    pub async fn introspect(&mut self) -> Result<TokenInfo> {
+        let access_token = self.get_access_token().await?;
+        token_info::introspect_with_client(&self.http_client(), &access_token).await
+    }
+
+    /// Get an ID token for the service account, with the given audience in the `aud` claim.
+    ///
+    /// * `audience` -  The URL of the receiving service, for example a Cloud Run service or an IAP protected resource.
+    #[tracing::instrument(skip_all, fields(client_email = %self.client_email, status, latency_ms))]
+    pub async fn get_id_token(&self, audience: &str) -> Result<String> {
+        let jwt = self.make_id_token_assertion(audience)?;
+        let v = self.post_assertion(&jwt).await?;
+        if let Some(id_token) = v["id_token"].as_str() {
+            Ok(id_token.to_owned())
+        } else {
+            bail!("Error parsing for id token!")
+        }
+    }
+
+    /// Expiration time of the cached access token as a unix timestamp, if one has been fetched.
+    pub fn token_expiration_time(&self) -> Option<i64> {
+        self.token.as_ref().map(|token| token.expiration_time)
+    }
+
+    /// Email address of the service account.
+    pub fn client_email(&self) -> &str {
+        &self.client_email
+    }
+
+    /// Service account and subject the access tokens are issued for, to tell callers apart.
+    #[cfg(any(feature = "translate", feature = "routes"))]
+    pub(crate) fn identity(&self) -> String {
+        format!("{}/{}", self.client_email, self.sub.as_deref().unwrap_or_default())
+    }
+
+    /// Id of the project the service account belongs to.
+    pub fn project_id(&self) -> &str {
+        &self.project_id
+    }
+
+    /// Check that the credentials can be used to sign assertions.
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.client_id.is_empty() || self.token_uri.is_empty() {
+            bail!("Invalid credentials! client_id and token_uri are required.")
+        }
+        EncodingKey::from_rsa_pem(self.private_key.as_bytes())?;
+        Ok(())
+    }
+
     /// Add scopes to request the access token for.
     ///
     /// * `scopes` -  Scopes that your application needs access to. [OAuth 2.0 Scopes](https://developers.google.com/identity/protocols/oauth2/scopes)
@@ -63,7 +134,7 @@ impl ServiceAccountCredentials {
         let mut scoped_credentials = self.clone();
         scoped_credentials.scopes = Some(scopes.into_iter().map(|s| s.to_owned()).collect());
         scoped_credentials.token = None;
-        return scoped_credentials
+        scoped_credentials
     }
 
     /// Add subject to grants your application delegated access to a resource.
@@ -73,10 +144,29 @@ impl ServiceAccountCredentials {
         let mut subjected_credential = self.clone();
         subjected_credential.sub = Some(subject.to_owned());
         subjected_credential.token = None;
-        return subjected_credential
+        subjected_credential
+    }
+
+    /// Use the given HTTP client for token requests instead of creating a new one per request.
+    ///
+    /// * `client` -  `reqwest::Client` to use. See `ClientConfig` to build a configured client.
+    pub fn with_http_client(&self, client: Client) -> Self {
+        let mut credentials = self.clone();
+        credentials.client = Some(client);
+        credentials
+    }
+
+    /// Use the given HTTP client for token requests, unless one was set with `with_http_client`.
+    #[cfg(any(feature = "translate", feature = "routes"))]
+    pub(crate) fn with_default_http_client(&self, client: Client) -> Self {
+        match self.client {
+            Some(_) => self.clone(),
+            None => self.with_http_client(client),
+        }
     }
 
     /// Get an access token for the service account using the scopes and subject specified.
+    #[tracing::instrument(skip_all, fields(client_email = %self.client_email, refreshed, status, latency_ms))]
     pub async fn get_access_token(&mut self) -> Result<String> {
         let now = Local::now();
         let iat = now.timestamp();
@@ -84,6 +174,7 @@ impl ServiceAccountCredentials {
         match self.token.clone() {
             Some(token) => {
                 if iat > token.expiration_time {
+                    Span::current().record("refreshed", true);
                     let jwt = self.make_assertion()?;
                     let access_token = self.request_token(&jwt).await?;
                     self.token = Some(Token{
@@ -92,10 +183,12 @@ impl ServiceAccountCredentials {
                     });
                     return Ok(access_token);
                 } else {
+                    Span::current().record("refreshed", false);
                     return Ok(token.access_token.clone());
                 }
             },
             None => {
+                Span::current().record("refreshed", true);
                 let jwt = self.make_assertion()?;
                 let access_token = self.request_token(&jwt).await?;
                 self.token = Some(Token{
@@ -107,6 +200,14 @@ impl ServiceAccountCredentials {
         };
     }
 
+    /// Whether a cached access token exists and has not expired.
+    pub(crate) fn has_valid_token(&self) -> bool {
+        match &self.token {
+            Some(token) => Local::now().timestamp() <= token.expiration_time,
+            None => false,
+        }
+    }
+
     fn make_assertion(&self) -> Result<String> {
         let scope: String = match self.scopes.clone() {
             Some(scopes) => {
@@ -139,12 +240,49 @@ impl ServiceAccountCredentials {
             &EncodingKey::from_rsa_pem(self.private_key.as_bytes())?,
         )?;
 
-        return Ok(jwt);
+        Ok(jwt)
     }
 
 
+    fn make_id_token_assertion(&self, audience: &str) -> Result<String> {
+        let mut header = Header::new(Algorithm::RS256);
+        header.typ = Some("JWT".to_owned());
+        header.kid = Some(self.private_key_id.clone());
+
+        let now = Local::now();
+        let claims = IdTokenClaims {
+            iss: self.client_email.clone(),
+            sub: self.client_email.clone(),
+            aud: self.token_uri.clone(),
+            target_audience: audience.to_owned(),
+            iat: now.timestamp(),
+            exp: (now + Duration::hours(1)).timestamp(),
+        };
+
+        let jwt = encode(
+            &header,
+            &claims,
+            &EncodingKey::from_rsa_pem(self.private_key.as_bytes())?,
+        )?;
+
+        Ok(jwt)
+    }
+
     async fn request_token(&self, assertion: &str) -> Result<String> {
-        let client = Client::new();
+        let result = self.post_assertion(assertion).await.and_then(|v| match v["access_token"].as_str() {
+            Some(access_token) => Ok(access_token.to_owned()),
+            None => Err(anyhow!("Error parsing for access token!")),
+        });
+        crate::metrics::record_token_refresh(result.is_ok());
+        result
+    }
+
+    fn http_client(&self) -> Client {
+        self.client.clone().unwrap_or_else(default_http_client)
+    }
+
+    async fn post_assertion(&self, assertion: &str) -> Result<Value> {
+        let client = self.http_client();
         let mut headers = HeaderMap::new();
         headers.insert(
             CONTENT_TYPE,
@@ -158,6 +296,7 @@ impl ServiceAccountCredentials {
             ("grant_type", &grant_type)
         ]);
 
+        let started = Instant::now();
         let response = client
             .post(self.token_uri.clone())
             .headers(headers.clone())
@@ -166,6 +305,8 @@ impl ServiceAccountCredentials {
             .await?;
 
         let status_code = response.status();
+        Span::current().record("status", status_code.as_u16());
+        Span::current().record("latency_ms", started.elapsed().as_millis() as u64);
         let body: String = response.text().await?;
 
         if !status_code.is_success() {
@@ -173,16 +314,19 @@ impl ServiceAccountCredentials {
             bail!(format!("Response Error: {}! Message: {}", error_response.error, error_response.error_description));
         }
 
-        let v: Value = serde_json::from_str(&body)?;
-        if let Some(access_token) = v["access_token"].as_str() {
-            return Ok(access_token.to_owned());
-        } else {
-            bail!("Error parsing for access token!")
-        }
+        Ok(serde_json::from_str::<Value>(&body)?)
     }
 }
 
 
+/// Client of token requests made without a client set with `with_http_client`,
+/// shared so that they reuse its connection pool.
+pub(crate) fn default_http_client() -> Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(Client::new).clone()
+}
+
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     iss: String,
@@ -193,3 +337,73 @@ struct Claims {
     iat: i64,
     exp: i64,
 }
+
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    aud: String,
+    target_audience: String,
+    iat: i64,
+    exp: i64,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::auth::token_info::tests::respond_once;
+
+    fn credentials_with_token(access_token: &str) -> ServiceAccountCredentials {
+        let credentials = json!({
+            "type": "service_account",
+            "project_id": "test-project",
+            "private_key_id": "test-key-id",
+            "private_key": include_str!("../../tests/data/mtls/rsa_key.pem"),
+            "client_email": "test@test-project.iam.gserviceaccount.com",
+            "client_id": "1234567890",
+            "auth_uri": "https://accounts.google.com/o/oauth2/auth",
+            "token_uri": "https://oauth2.googleapis.com/token",
+            "auth_provider_x509_cert_url": "https://www.googleapis.com/oauth2/v1/certs",
+            "client_x509_cert_url": "https://www.googleapis.com/robot/v1/metadata/x509/test",
+            "universe_domain": "googleapis.com",
+            "token": { "expiration_time": (Local::now() + Duration::minutes(30)).timestamp(), "access_token": access_token },
+        });
+        ServiceAccountCredentials::from_service_account_info(credentials.to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn revoke_clears_the_cached_token() {
+        let mut credentials = credentials_with_token("cached-token");
+        let (url, request) = respond_once("200 OK", "{}").await;
+
+        credentials.revoke_at(&url).await.unwrap();
+
+        assert!(request.await.unwrap().ends_with("token=cached-token"));
+        assert!(!credentials.has_valid_token());
+        assert_eq!(credentials.token_expiration_time(), None);
+    }
+
+    #[tokio::test]
+    async fn failed_revoke_keeps_the_cached_token() {
+        let mut credentials = credentials_with_token("cached-token");
+        let (url, _) = respond_once("503 Service Unavailable", r#"{ "error": "backend_error", "error_description": "Try again later" }"#).await;
+
+        let error = credentials.revoke_at(&url).await.unwrap_err();
+
+        assert_eq!(error.to_string(), "Response Error: backend_error! Message: Try again later");
+        assert!(credentials.has_valid_token());
+        assert_eq!(credentials.get_access_token().await.unwrap(), "cached-token");
+    }
+
+    #[tokio::test]
+    async fn revoke_without_token_sends_nothing() {
+        let mut credentials = credentials_with_token("cached-token");
+        credentials.token = None;
+
+        credentials.revoke_at("http://127.0.0.1:9/").await.unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::auth_error::AuthErrorResponse;
+use super::service_account;
+
+static TOKEN_INFO_URL: &str = "https://oauth2.googleapis.com/tokeninfo";
+pub(super) static REVOKE_URL: &str = "https://oauth2.googleapis.com/revoke";
+
+
+/// Information about an access token, as reported by the tokeninfo endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenInfo {
+    /// Scopes granted to the token.
+    pub scopes: Vec<String>,
+    /// Time the token expires.
+    pub expiration_time: Option<DateTime<Utc>>,
+    /// Client the token was issued to.
+    pub audience: Option<String>,
+    /// Authorized party, the client that requested the token.
+    pub authorized_party: Option<String>,
+    /// Subject of the token.
+    pub subject: Option<String>,
+    /// Email of the user or service account, if the token was granted the email scope.
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+}
+
+impl TokenInfo {
+    /// Whether the token was granted the scope.
+    ///
+    /// * `scope` -  Scope to look for. [OAuth 2.0 Scopes](https://developers.google.com/identity/protocols/oauth2/scopes)
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+
+// the tokeninfo endpoint returns all values as strings
+#[derive(Debug, Clone, Deserialize)]
+struct TokenInfoResponse {
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    exp: Option<String>,
+    #[serde(default)]
+    aud: Option<String>,
+    #[serde(default)]
+    azp: Option<String>,
+    #[serde(default)]
+    sub: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: Option<String>,
+}
+
+impl From<TokenInfoResponse> for TokenInfo {
+    fn from(response: TokenInfoResponse) -> Self {
+        Self {
+            scopes: response.scope.map(|scope| scope.split_whitespace().map(|s| s.to_owned()).collect()).unwrap_or_default(),
+            expiration_time: response.exp.and_then(|exp| exp.parse::<i64>().ok()).and_then(|exp| DateTime::from_timestamp(exp, 0)),
+            audience: response.aud,
+            authorized_party: response.azp,
+            subject: response.sub,
+            email: response.email,
+            email_verified: response.email_verified.map(|verified| verified == "true"),
+        }
+    }
+}
+
+
+/// Get the scopes, expiry, audience and email of an access token. <br>
+/// See https://developers.google.com/identity/protocols/oauth2#validate-access-token
+///
+/// * `access_token` -  Access token to inspect.
+pub async fn introspect(access_token: &str) -> Result<TokenInfo> {
+    introspect_with_client(&service_account::default_http_client(), access_token).await
+}
+
+/// Same as `introspect`, using the given HTTP client.
+///
+/// * `client` -  `reqwest::Client` to send the request with.
+/// * `access_token` -  Access token to inspect.
+pub async fn introspect_with_client(client: &Client, access_token: &str) -> Result<TokenInfo> {
+    introspect_at(client, TOKEN_INFO_URL, access_token).await
+}
+
+async fn introspect_at(client: &Client, url: &str, access_token: &str) -> Result<TokenInfo> {
+    let response = client
+        .get(url)
+        .query(&[("access_token", access_token)])
+        .send()
+        .await?;
+
+    let status_code = response.status();
+    let body: String = response.text().await?;
+
+    if !status_code.is_success() {
+        let error_response: AuthErrorResponse = serde_json::from_str(&body).unwrap_or_default();
+        bail!(format!("Response Error: {}! Message: {}", error_response.error, error_response.error_description));
+    }
+
+    Ok(serde_json::from_str::<TokenInfoResponse>(&body)?.into())
+}
+
+
+/// Revoke an access token or a refresh token. <br>
+/// See https://developers.google.com/identity/protocols/oauth2/web-server#tokenrevoke
+///
+/// * `token` -  Access token or refresh token to revoke.
+pub async fn revoke(token: &str) -> Result<()> {
+    revoke_with_client(&service_account::default_http_client(), token).await
+}
+
+/// Same as `revoke`, using the given HTTP client.
+///
+/// * `client` -  `reqwest::Client` to send the request with.
+/// * `token` -  Access token or refresh token to revoke.
+pub async fn revoke_with_client(client: &Client, token: &str) -> Result<()> {
+    revoke_at(client, REVOKE_URL, token).await
+}
+
+pub(super) async fn revoke_at(client: &Client, url: &str, token: &str) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/x-www-form-urlencoded"),
+    );
+
+    let response = client
+        .post(url)
+        .headers(headers)
+        .body(url_encoded_data::stringify(&[("token", token)]))
+        .send()
+        .await?;
+
+    let status_code = response.status();
+    let body: String = response.text().await?;
+
+    if !status_code.is_success() {
+        let error_response: AuthErrorResponse = serde_json::from_str(&body).unwrap_or_default();
+        bail!(format!("Response Error: {}! Message: {}", error_response.error, error_response.error_description));
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+pub(super) mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    use super::*;
+
+    /// Answer a single HTTP request with the status and JSON body, and return the URL to send it to
+    /// and a handle resolving to the request received.
+    pub(in crate::auth) async fn respond_once(status: &str, body: &str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, body.len(), body
+        );
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                    let content_length = headers
+                        .lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|length| length.trim().parse::<usize>().unwrap()))
+                        .unwrap_or_default();
+                    if body.len() >= content_length {
+                        break;
+                    }
+                }
+                if read == 0 {
+                    break;
+                }
+            }
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn token_info_of_response() {
+        let body = r#"{
+            "azp": "1234567890.apps.googleusercontent.com",
+            "aud": "1234567890.apps.googleusercontent.com",
+            "sub": "1122334455",
+            "scope": "https://www.googleapis.com/auth/cloud-platform https://www.googleapis.com/auth/userinfo.email",
+            "exp": "1735689600",
+            "expires_in": "3599",
+            "email": "test@test-project.iam.gserviceaccount.com",
+            "email_verified": "true",
+            "access_type": "online"
+        }"#;
+        let (url, request) = respond_once("200 OK", body).await;
+
+        let token_info = introspect_at(&Client::new(), &url, "test-token").await.unwrap();
+
+        assert!(request.await.unwrap().starts_with("GET /?access_token=test-token "));
+        assert_eq!(token_info.scopes, ["https://www.googleapis.com/auth/cloud-platform", "https://www.googleapis.com/auth/userinfo.email"]);
+        assert!(token_info.has_scope("https://www.googleapis.com/auth/cloud-platform"));
+        assert!(!token_info.has_scope("https://www.googleapis.com/auth/drive"));
+        assert_eq!(token_info.expiration_time, DateTime::from_timestamp(1_735_689_600, 0));
+        assert_eq!(token_info.audience.as_deref(), Some("1234567890.apps.googleusercontent.com"));
+        assert_eq!(token_info.authorized_party.as_deref(), Some("1234567890.apps.googleusercontent.com"));
+        assert_eq!(token_info.subject.as_deref(), Some("1122334455"));
+        assert_eq!(token_info.email.as_deref(), Some("test@test-project.iam.gserviceaccount.com"));
+        assert_eq!(token_info.email_verified, Some(true));
+    }
+
+    #[tokio::test]
+    async fn token_info_without_optional_fields() {
+        let (url, _) = respond_once("200 OK", r#"{ "exp": "not a number" }"#).await;
+
+        let token_info = introspect_at(&Client::new(), &url, "test-token").await.unwrap();
+
+        assert!(token_info.scopes.is_empty());
+        assert_eq!((token_info.expiration_time, token_info.email, token_info.email_verified), (None, None, None));
+    }
+
+    #[tokio::test]
+    async fn introspect_error() {
+        let (url, _) = respond_once("400 Bad Request", r#"{ "error": "invalid_token", "error_description": "Invalid Value" }"#).await;
+
+        let error = introspect_at(&Client::new(), &url, "test-token").await.unwrap_err();
+
+        assert_eq!(error.to_string(), "Response Error: invalid_token! Message: Invalid Value");
+    }
+
+    #[tokio::test]
+    async fn revoke_posts_the_token() {
+        let (url, request) = respond_once("200 OK", "{}").await;
+
+        revoke_at(&Client::new(), &url, "test-token").await.unwrap();
+
+        let request = request.await.unwrap();
+        assert!(request.starts_with("POST / "));
+        assert!(request.ends_with("\r\n\r\ntoken=test-token"));
+    }
+
+    #[tokio::test]
+    async fn revoke_error() {
+        let (url, _) = respond_once("400 Bad Request", r#"{ "error": "invalid_token", "error_description": "Token expired or revoked" }"#).await;
+
+        let error = revoke_at(&Client::new(), &url, "test-token").await.unwrap_err();
+
+        assert_eq!(error.to_string(), "Response Error: invalid_token! Message: Token expired or revoked");
+    }
+}
@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use reqwest::Client;
+use tokio::sync::watch;
+use tokio::{fs, time};
+
+use super::service_account::ServiceAccountCredentials;
+
+static DEFAULT_POLL_INTERVAL_SECONDS: u64 = 30;
+
+/// `ServiceAccountCredentials` backed by a key file that is watched for changes.
+///
+/// When the file changes on disk (for example, a rotated Kubernetes secret), the new key is loaded and validated,
+/// and subsequent token requests use it. If the new file cannot be parsed or the key is invalid, the previous key is kept.
+#[derive(Debug, Clone)]
+pub struct WatchedServiceAccountCredentials {
+    receiver: watch::Receiver<ServiceAccountCredentials>,
+    credentials: ServiceAccountCredentials,
+    scopes: Option<Vec<String>>,
+    sub: Option<String>,
+    client: Option<Client>,
+}
+
+impl WatchedServiceAccountCredentials {
+    /// Create `WatchedServiceAccountCredentials` from file, checking the file for changes every 30 seconds.
+    /// Must be called from within a tokio runtime.
+    ///
+    /// * `filepath` -  File path to the service account credential file. File should be valid JSON.
+    pub fn from_service_account_file(filepath: PathBuf) -> Result<Self> {
+        Self::from_service_account_file_with_interval(filepath, Duration::from_secs(DEFAULT_POLL_INTERVAL_SECONDS))
+    }
+
+    /// Create `WatchedServiceAccountCredentials` from file, checking the file for changes at the given interval.
+    /// Must be called from within a tokio runtime.
+    ///
+    /// * `filepath` -  File path to the service account credential file. File should be valid JSON.
+    /// * `poll_interval` -  How often to check the file for changes.
+    pub fn from_service_account_file_with_interval(filepath: PathBuf, poll_interval: Duration) -> Result<Self> {
+        let credentials_json = std::fs::read_to_string(&filepath)?;
+        let credentials = ServiceAccountCredentials::from_service_account_info(credentials_json.clone())?;
+        credentials.validate()?;
+
+        let (sender, receiver) = watch::channel(credentials.clone());
+        tokio::spawn(watch_credentials_file(filepath, poll_interval, credentials_json, sender));
+
+        Ok(Self { receiver, credentials, scopes: None, sub: None, client: None })
+    }
+
+    /// Add scopes to request the access token for. Scopes are kept when the key file is reloaded.
+    ///
+    /// * `scopes` -  Scopes that your application needs access to. [OAuth 2.0 Scopes](https://developers.google.com/identity/protocols/oauth2/scopes)
+    pub fn with_scopes(&self, scopes: Vec<&str>) -> Self {
+        let mut scoped_credentials = self.clone();
+        scoped_credentials.scopes = Some(scopes.iter().map(|s| s.to_string()).collect());
+        scoped_credentials.credentials = self.credentials.with_scopes(scopes);
+        scoped_credentials
+    }
+
+    /// Add subject to grants your application delegated access to a resource. Subject is kept when the key file is reloaded.
+    ///
+    /// * `subject` -  The email address of the user for which the application is requesting delegated access.
+    pub fn with_subject(&self, subject: &str) -> Self {
+        let mut subjected_credentials = self.clone();
+        subjected_credentials.sub = Some(subject.to_owned());
+        subjected_credentials.credentials = self.credentials.with_subject(subject);
+        subjected_credentials
+    }
+
+    /// Use the given HTTP client for token requests. The client is kept when the key file is reloaded.
+    ///
+    /// * `client` -  `reqwest::Client` to use. See `ClientConfig` to build a configured client.
+    pub fn with_http_client(&self, client: Client) -> Self {
+        let mut credentials = self.clone();
+        credentials.credentials = self.credentials.with_http_client(client.clone());
+        credentials.client = Some(client);
+        credentials
+    }
+
+    /// Use the given HTTP client for token requests, unless one was set with `with_http_client`.
+    #[cfg(any(feature = "translate", feature = "routes"))]
+    pub(crate) fn with_default_http_client(&self, client: Client) -> Self {
+        match self.client {
+            Some(_) => self.clone(),
+            None => self.with_http_client(client),
+        }
+    }
+
+    /// Service account and subject the access tokens are issued for, to tell callers apart.
+    #[cfg(any(feature = "translate", feature = "routes"))]
+    pub(crate) fn identity(&self) -> String {
+        self.credentials.identity()
+    }
+
+    /// Get an access token using the latest key loaded from the watched file.
+    pub async fn get_access_token(&mut self) -> Result<String> {
+        if self.receiver.has_changed().unwrap_or(false) {
+            let credentials = self.receiver.borrow_and_update().clone();
+            self.credentials = self.apply_scopes_and_subject(credentials);
+        }
+        self.credentials.get_access_token().await
+    }
+
+    fn apply_scopes_and_subject(&self, mut credentials: ServiceAccountCredentials) -> ServiceAccountCredentials {
+        if let Some(scopes) = &self.scopes {
+            credentials = credentials.with_scopes(scopes.iter().map(|s| s.as_str()).collect());
+        }
+        if let Some(sub) = &self.sub {
+            credentials = credentials.with_subject(sub);
+        }
+        if let Some(client) = &self.client {
+            credentials = credentials.with_http_client(client.clone());
+        }
+        credentials
+    }
+}
+
+
+async fn watch_credentials_file(filepath: PathBuf, poll_interval: Duration, mut current_json: String, sender: watch::Sender<ServiceAccountCredentials>) {
+    let mut interval = time::interval(poll_interval);
+    // first tick completes immediately
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        if sender.is_closed() {
+            break;
+        }
+
+        // the file might be missing for a moment while a mounted secret is being swapped
+        let Ok(credentials_json) = fs::read_to_string(&filepath).await else {
+            continue;
+        };
+        if credentials_json == current_json {
+            continue;
+        }
+
+        let Ok(credentials) = ServiceAccountCredentials::from_service_account_info(credentials_json.clone()) else {
+            continue;
+        };
+        if credentials.validate().is_err() {
+            continue;
+        }
+
+        current_json = credentials_json;
+        if sender.send(credentials).is_err() {
+            break;
+        }
+    }
+}
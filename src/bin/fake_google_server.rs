@@ -0,0 +1,17 @@
+use std::{env, net::SocketAddr, str::FromStr};
+
+use anyhow::Result;
+use google_api_rust_client_unoffical::testing::FakeGoogleServer;
+
+static DEFAULT_ADDR: &str = "127.0.0.1:8990";
+
+/// Serves fake Cloud Translation and Routes APIs on `FAKE_GOOGLE_SERVER_ADDR` (default 127.0.0.1:8990).
+/// Point the services at it with `GOOGLE_TRANSLATE_ENDPOINT=http://127.0.0.1:8990` and `GOOGLE_ROUTES_ENDPOINT=http://127.0.0.1:8990`.
+#[tokio::main]
+async fn main() -> Result<()> {
+    let addr = env::var("FAKE_GOOGLE_SERVER_ADDR").unwrap_or(DEFAULT_ADDR.to_owned());
+    let addr = SocketAddr::from_str(&addr)?;
+
+    println!("Serving fake Google APIs on http://{}", addr);
+    FakeGoogleServer::new().serve(addr).await
+}
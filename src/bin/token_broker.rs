@@ -0,0 +1,24 @@
+use std::{env, net::SocketAddr, path::PathBuf, str::FromStr};
+
+use anyhow::{Context, Result};
+use google_api_rust_client_unoffical::{auth::service_account::ServiceAccountCredentials, token_broker::TokenBroker};
+
+static DEFAULT_ADDR: &str = "127.0.0.1:8989";
+
+/// Serves tokens for the service account in `GOOGLE_APPLICATION_CREDENTIALS` (or the first argument)
+/// on `TOKEN_BROKER_ADDR` (default 127.0.0.1:8989).
+/// Point clients at it with `GCE_METADATA_HOST=127.0.0.1:8989`.
+#[tokio::main]
+async fn main() -> Result<()> {
+    let filepath = env::args()
+        .nth(1)
+        .or_else(|| env::var("GOOGLE_APPLICATION_CREDENTIALS").ok())
+        .context("Usage: token-broker <credentials.json>, or set GOOGLE_APPLICATION_CREDENTIALS")?;
+    let addr = env::var("TOKEN_BROKER_ADDR").unwrap_or(DEFAULT_ADDR.to_owned());
+    let addr = SocketAddr::from_str(&addr)?;
+
+    let credentials = ServiceAccountCredentials::from_service_account_file(PathBuf::from(filepath))?;
+    println!("Serving tokens for {} on http://{}", credentials.client_email(), addr);
+
+    TokenBroker::new(credentials).serve(addr).await
+}
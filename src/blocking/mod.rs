@@ -0,0 +1,33 @@
+//! Synchronous versions of the services and credentials, for programs that do not run an async runtime.
+//!
+//! Each blocking type runs its async counterpart on its own single-threaded tokio runtime.
+//! They must not be used from within an async runtime, as blocking on a future there panics.
+
+pub mod service_account;
+#[cfg(feature = "translate")]
+pub mod translate_service;
+#[cfg(feature = "routes")]
+pub mod route_service;
+
+use std::future::Future;
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::runtime::{Builder, Runtime};
+
+
+#[derive(Debug, Clone)]
+struct BlockingRuntime {
+    runtime: Arc<Runtime>,
+}
+
+impl BlockingRuntime {
+    fn new() -> Result<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { runtime: Arc::new(runtime) })
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
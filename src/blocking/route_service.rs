@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::auth::service_account::ServiceAccountCredentials;
+use crate::services::route_service;
+use crate::services::route_service::common_models::WayPoint;
+use crate::services::route_service::get_route::response_model::ComputeRouteResponse;
+use crate::services::route_service::get_route_matrix::request_model::RouteMatrixOrigin;
+use crate::services::route_service::get_route_matrix::response_model::ComputeRouteMatrixResponse;
+use super::BlockingRuntime;
+
+
+/// Synchronous wrapper of `RouteService`.
+#[derive(Debug, Clone)]
+pub struct RouteService {
+    inner: route_service::RouteService,
+    runtime: BlockingRuntime,
+}
+
+impl RouteService {
+    /// Create blocking `RouteService` from a configured async `RouteService`.
+    ///
+    /// * `service` -  `RouteService` to run synchronously.
+    pub fn new(service: route_service::RouteService) -> Result<Self> {
+        Ok(Self { inner: service, runtime: BlockingRuntime::new()? })
+    }
+
+    /// Create blocking `RouteService` Authenticate by using API keys.
+    ///
+    /// * `api_key` -  API key to use to authenticate to Google Cloud APIs and services that support API keys.
+    pub fn new_with_api_key(api_key: String) -> Result<Self> {
+        Self::new(route_service::RouteService::new_with_api_key(api_key))
+    }
+
+    /// Create blocking `RouteService` Authenticate by using service account credentials.
+    ///
+    /// * `service_account_credentials` -  `ServiceAccountCredentials` to use to authenticate to Google Cloud APIs.
+    pub fn new_with_credentials(service_account_credentials: ServiceAccountCredentials) -> Result<Self> {
+        Self::new(route_service::RouteService::new_with_credentials(service_account_credentials))
+    }
+
+    /// Get a route. See `RouteService::get_route`.
+    pub fn get_route(&mut self, origin: &WayPoint, destination: &WayPoint, response_masks: Option<Vec<&str>>, params: Option<HashMap<String, Value>>) -> Result<ComputeRouteResponse> {
+        self.runtime.block_on(self.inner.get_route(origin, destination, response_masks, params))
+    }
+
+    /// Calculate the distance and duration of a route for multiple origins and destinations. See `RouteService::get_route_matrix`.
+    pub fn get_route_matrix(&mut self, origin: &Vec<RouteMatrixOrigin>, destination: &Vec<RouteMatrixOrigin>, response_masks: Option<Vec<&str>>, params: Option<HashMap<String, Value>>) -> Result<Vec<ComputeRouteMatrixResponse>> {
+        self.runtime.block_on(self.inner.get_route_matrix(origin, destination, response_masks, params))
+    }
+}
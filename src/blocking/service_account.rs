@@ -0,0 +1,63 @@
+use anyhow::Result;
+
+use crate::auth::service_account;
+use crate::auth::token_info::TokenInfo;
+use super::BlockingRuntime;
+
+
+/// Synchronous wrapper of `ServiceAccountCredentials`.
+#[derive(Debug, Clone)]
+pub struct ServiceAccountCredentials {
+    inner: service_account::ServiceAccountCredentials,
+    runtime: BlockingRuntime,
+}
+
+impl ServiceAccountCredentials {
+    /// Create blocking `ServiceAccountCredentials`.
+    ///
+    /// * `credentials` -  `ServiceAccountCredentials` created with `from_service_account_file` or `from_service_account_info`.
+    pub fn new(credentials: service_account::ServiceAccountCredentials) -> Result<Self> {
+        Ok(Self { inner: credentials, runtime: BlockingRuntime::new()? })
+    }
+
+    /// Add scopes to request the access token for.
+    ///
+    /// * `scopes` -  Scopes that your application needs access to. [OAuth 2.0 Scopes](https://developers.google.com/identity/protocols/oauth2/scopes)
+    pub fn with_scopes(&self, scopes: Vec<&str>) -> Self {
+        Self { inner: self.inner.with_scopes(scopes), runtime: self.runtime.clone() }
+    }
+
+    /// Add subject to grants your application delegated access to a resource.
+    ///
+    /// * `subject` -  The email address of the user for which the application is requesting delegated access.
+    pub fn with_subject(&self, subject: &str) -> Self {
+        Self { inner: self.inner.with_subject(subject), runtime: self.runtime.clone() }
+    }
+
+    /// Get an access token for the service account using the scopes and subject specified.
+    pub fn get_access_token(&mut self) -> Result<String> {
+        self.runtime.block_on(self.inner.get_access_token())
+    }
+
+    /// Get an ID token for the service account, with the given audience in the `aud` claim.
+    ///
+    /// * `audience` -  The URL of the receiving service.
+    pub fn get_id_token(&self, audience: &str) -> Result<String> {
+        self.runtime.block_on(self.inner.get_id_token(audience))
+    }
+
+    /// Get the scopes, expiry, audience and email of the current access token, requesting one if needed.
+    pub fn introspect(&mut self) -> Result<TokenInfo> {
+        self.runtime.block_on(self.inner.introspect())
+    }
+
+    /// Revoke the cached access token, if any.
+    pub fn revoke(&mut self) -> Result<()> {
+        self.runtime.block_on(self.inner.revoke())
+    }
+
+    /// The underlying async `ServiceAccountCredentials`, for example to create a blocking service with.
+    pub fn into_inner(self) -> service_account::ServiceAccountCredentials {
+        self.inner
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::auth::service_account::ServiceAccountCredentials;
+use crate::services::translate_service;
+use crate::services::translate_service::detect_language::DetectLanguageResponse;
+use crate::services::translate_service::list_languages::ListLanguageResponse;
+use crate::services::translate_service::translate_text::TranslateTextResponse;
+use super::BlockingRuntime;
+
+
+/// Synchronous wrapper of `TranslateService`.
+#[derive(Debug, Clone)]
+pub struct TranslateService {
+    inner: translate_service::TranslateService,
+    runtime: BlockingRuntime,
+}
+
+impl TranslateService {
+    /// Create blocking `TranslateService` from a configured async `TranslateService`.
+    ///
+    /// * `service` -  `TranslateService` to run synchronously.
+    pub fn new(service: translate_service::TranslateService) -> Result<Self> {
+        Ok(Self { inner: service, runtime: BlockingRuntime::new()? })
+    }
+
+    /// Create blocking `TranslateService` Authenticate by using API keys.
+    ///
+    /// * `api_key` -  API key to use to authenticate to Google Cloud APIs and services that support API keys.
+    pub fn new_with_api_key(api_key: String) -> Result<Self> {
+        Self::new(translate_service::TranslateService::new_with_api_key(api_key))
+    }
+
+    /// Create blocking `TranslateService` Authenticate by using service account credentials.
+    ///
+    /// * `service_account_credentials` -  `ServiceAccountCredentials` to use to authenticate to Google Cloud APIs.
+    pub fn new_with_credentials(service_account_credentials: ServiceAccountCredentials) -> Result<Self> {
+        Self::new(translate_service::TranslateService::new_with_credentials(service_account_credentials))
+    }
+
+    /// Translates text into the target language. See `TranslateService::translate`.
+    pub fn translate(&mut self, text: Vec<&str>, target: &str, params: Option<HashMap<String, Value>>) -> Result<TranslateTextResponse> {
+        self.runtime.block_on(self.inner.translate(text, target, params))
+    }
+
+    /// Detects the language of texts. See `TranslateService::detect_language`.
+    pub fn detect_language(&mut self, text: Vec<&str>) -> Result<DetectLanguageResponse> {
+        self.runtime.block_on(self.inner.detect_language(text))
+    }
+
+    /// List supported languages. See `TranslateService::list_languages`.
+    pub fn list_languages(&mut self, target: Option<&str>, model: Option<&str>) -> Result<ListLanguageResponse> {
+        self.runtime.block_on(self.inner.list_languages(target, model))
+    }
+}
@@ -1,2 +1,13 @@
 pub mod auth;
-pub mod services;
\ No newline at end of file
+#[cfg(any(feature = "translate", feature = "routes"))]
+pub mod services;
+pub mod metrics;
+
+#[cfg(feature = "broker")]
+pub mod token_broker;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+#[cfg(feature = "testing")]
+pub mod testing;
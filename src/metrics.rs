@@ -0,0 +1,88 @@
+//! OpenTelemetry metrics for API calls and token refreshes, recorded when the `opentelemetry` feature is enabled.
+//!
+//! Instruments are created from the global meter provider, so metrics are exported by whatever provider the application installs.
+//! * `google_api_client.requests` - counter of API calls, by `service`, `method` and `status_code` (0 if no response was received).
+//! * `google_api_client.request.duration` - histogram of API call latency in seconds, including retries, with the same attributes.
+//! * `google_api_client.token.refreshes` - counter of access token requests.
+//! * `google_api_client.token.refresh_failures` - counter of failed access token requests.
+
+#[cfg(feature = "opentelemetry")]
+mod instruments {
+    use std::sync::OnceLock;
+
+    use opentelemetry::global;
+    use opentelemetry::metrics::Counter;
+
+    static METER_NAME: &str = env!("CARGO_PKG_NAME");
+
+    // requests are only recorded by the services
+    struct Instruments {
+        #[cfg(any(feature = "translate", feature = "routes"))]
+        requests: Counter<u64>,
+        #[cfg(any(feature = "translate", feature = "routes"))]
+        request_duration: opentelemetry::metrics::Histogram<f64>,
+        token_refreshes: Counter<u64>,
+        token_refresh_failures: Counter<u64>,
+    }
+
+    fn instruments() -> &'static Instruments {
+        static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+        INSTRUMENTS.get_or_init(|| {
+            let meter = global::meter(METER_NAME);
+            Instruments {
+                #[cfg(any(feature = "translate", feature = "routes"))]
+                requests: meter
+                    .u64_counter("google_api_client.requests")
+                    .with_description("Number of API calls")
+                    .build(),
+                #[cfg(any(feature = "translate", feature = "routes"))]
+                request_duration: meter
+                    .f64_histogram("google_api_client.request.duration")
+                    .with_description("Latency of API calls, including retries")
+                    .with_unit("s")
+                    .build(),
+                token_refreshes: meter
+                    .u64_counter("google_api_client.token.refreshes")
+                    .with_description("Number of access token requests")
+                    .build(),
+                token_refresh_failures: meter
+                    .u64_counter("google_api_client.token.refresh_failures")
+                    .with_description("Number of failed access token requests")
+                    .build(),
+            }
+        })
+    }
+
+    #[cfg(any(feature = "translate", feature = "routes"))]
+    pub(crate) fn record_request(service: &'static str, method: &'static str, status_code: u16, duration: std::time::Duration) {
+        use opentelemetry::KeyValue;
+
+        let attributes = [
+            KeyValue::new("service", service),
+            KeyValue::new("method", method),
+            KeyValue::new("status_code", i64::from(status_code)),
+        ];
+        let instruments = instruments();
+        instruments.requests.add(1, &attributes);
+        instruments.request_duration.record(duration.as_secs_f64(), &attributes);
+    }
+
+    pub(crate) fn record_token_refresh(success: bool) {
+        let instruments = instruments();
+        instruments.token_refreshes.add(1, &[]);
+        if !success {
+            instruments.token_refresh_failures.add(1, &[]);
+        }
+    }
+}
+
+#[cfg(feature = "opentelemetry")]
+pub(crate) use instruments::record_token_refresh;
+#[cfg(all(feature = "opentelemetry", any(feature = "translate", feature = "routes")))]
+pub(crate) use instruments::record_request;
+
+#[cfg(all(not(feature = "opentelemetry"), any(feature = "translate", feature = "routes")))]
+pub(crate) fn record_request(_service: &'static str, _method: &'static str, _status_code: u16, _duration: std::time::Duration) {}
+
+#[cfg(not(feature = "opentelemetry"))]
+pub(crate) fn record_token_refresh(_success: bool) {}
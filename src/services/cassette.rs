@@ -0,0 +1,307 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use super::http_service::{self, BoxError, HttpRequest, HttpResponse};
+use super::http_transport::HttpTransport;
+
+
+/// Which parts of a request must be equal to those of a recorded request to replay its response.
+#[derive(Debug, Clone, Copy)]
+pub struct MatchRules {
+    method: bool,
+    url: bool,
+    body: bool,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self { method: true, url: true, body: true }
+    }
+}
+
+impl MatchRules {
+    /// Create `MatchRules` matching method, URL and body.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// * `method` -  Whether the HTTP method must match.
+    pub fn with_method(mut self, method: bool) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// * `url` -  Whether the URL must match, with API keys and tokens scrubbed.
+    pub fn with_url(mut self, url: bool) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// * `body` -  Whether the body must match. JSON bodies are compared as values, so formatting does not matter.
+    pub fn with_body(mut self, body: bool) -> Self {
+        self.body = body;
+        self
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, request: &RecordedRequest) -> bool {
+        (!self.method || recorded.method == request.method)
+            && (!self.url || recorded.url == request.url)
+            && (!self.body || body_value(&recorded.body) == body_value(&request.body))
+    }
+}
+
+fn body_value(body: &str) -> Value {
+    serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_owned()))
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl RecordedRequest {
+    // API keys, tokens and authorization headers are scrubbed before they are written to the cassette
+    fn new(request: &HttpRequest) -> Self {
+        Self {
+            method: request.method().to_string(),
+            url: http_service::redacted_uri(request.uri()),
+            headers: http_service::redacted_headers(request.headers()),
+            body: String::from_utf8_lossy(request.body()).into_owned(),
+        }
+    }
+}
+
+impl RecordedResponse {
+    fn new(response: &HttpResponse) -> Self {
+        Self {
+            status: response.status().as_u16(),
+            headers: http_service::redacted_headers(response.headers()),
+            body: String::from_utf8_lossy(response.body()).into_owned(),
+        }
+    }
+
+    fn to_response(&self) -> Result<HttpResponse> {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        Ok(builder.body(self.body.clone().into_bytes())?)
+    }
+}
+
+
+#[derive(Debug)]
+enum Mode {
+    Record(Arc<dyn HttpTransport>),
+    Replay,
+}
+
+/// `HttpTransport` that records requests and responses to a JSON cassette file, or replays them offline.
+///
+/// In record mode, requests are sent with the wrapped transport and every interaction is written to the cassette,
+/// with the `Authorization` and `X-goog-api-key` headers and the `key` and `access_token` query parameters scrubbed.
+/// In replay mode, each request is answered with the first recorded response not replayed yet whose request matches
+/// according to the `MatchRules`. Token requests of service account credentials are not sent through the transport,
+/// so use an API key when replaying.
+#[derive(Debug, Clone)]
+pub struct CassetteTransport {
+    path: PathBuf,
+    mode: Arc<Mode>,
+    match_rules: MatchRules,
+    interactions: Arc<Mutex<Vec<(Interaction, bool)>>>,
+    // keeps concurrent recordings from overwriting the file with an older snapshot
+    write_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl CassetteTransport {
+    /// Create `CassetteTransport` recording the interactions sent with `transport` to a new cassette file.
+    ///
+    /// * `path` -  Path of the cassette file, overwritten as interactions are recorded.
+    /// * `transport` -  `HttpTransport` to send requests with, such as `ReqwestTransport`.
+    pub fn record<T: HttpTransport + 'static>(path: impl AsRef<Path>, transport: T) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            mode: Arc::new(Mode::Record(Arc::new(transport))),
+            match_rules: MatchRules::default(),
+            interactions: Arc::new(Mutex::new(vec![])),
+            write_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Create `CassetteTransport` replaying the interactions of a cassette file.
+    ///
+    /// * `path` -  Path of the cassette file.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let cassette: Cassette = serde_json::from_str(&std::fs::read_to_string(path.as_ref())?)?;
+        Ok(Self {
+            path: path.as_ref().to_owned(),
+            mode: Arc::new(Mode::Replay),
+            match_rules: MatchRules::default(),
+            interactions: Arc::new(Mutex::new(cassette.interactions.into_iter().map(|interaction| (interaction, false)).collect())),
+            write_lock: Arc::new(tokio::sync::Mutex::new(())),
+        })
+    }
+
+    /// Set the parts of a request that must match a recorded request in replay mode. Defaults to method, URL and body.
+    ///
+    /// * `match_rules` -  `MatchRules` to use.
+    pub fn with_match_rules(mut self, match_rules: MatchRules) -> Self {
+        self.match_rules = match_rules;
+        self
+    }
+
+    async fn record_interaction(&self, transport: &Arc<dyn HttpTransport>, request: HttpRequest) -> Result<HttpResponse, BoxError> {
+        let recorded_request = RecordedRequest::new(&request);
+        let response = transport.send(request).await?;
+        let interaction = Interaction { request: recorded_request, response: RecordedResponse::new(&response) };
+
+        let _write_guard = self.write_lock.lock().await;
+        let cassette = {
+            let mut interactions = self.interactions.lock().map_err(|_| anyhow!("Cassette lock poisoned!"))?;
+            interactions.push((interaction, true));
+            Cassette { interactions: interactions.iter().map(|(interaction, _)| interaction.clone()).collect() }
+        };
+        tokio::fs::write(&self.path, serde_json::to_vec_pretty(&cassette)?).await?;
+        Ok(response)
+    }
+
+    fn replay_interaction(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let request = RecordedRequest::new(&request);
+        let mut interactions = self.interactions.lock().map_err(|_| anyhow!("Cassette lock poisoned!"))?;
+        let (interaction, replayed) = interactions
+            .iter_mut()
+            .find(|(interaction, replayed)| !*replayed && self.match_rules.matches(&interaction.request, &request))
+            .ok_or(anyhow!("No interaction in cassette {} matches {} {}", self.path.display(), request.method, request.url))?;
+        *replayed = true;
+        interaction.response.to_response()
+    }
+}
+
+#[async_trait]
+impl HttpTransport for CassetteTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, BoxError> {
+        match self.mode.as_ref() {
+            Mode::Record(transport) => self.record_interaction(transport, request).await,
+            Mode::Replay => Ok(self.replay_interaction(request)?),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct EchoTransport;
+
+    #[async_trait]
+    impl HttpTransport for EchoTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, BoxError> {
+            Ok(http::Response::builder().status(200).header("x-echo", "1").body(request.body().clone())?)
+        }
+    }
+
+    fn request(method: &str, url: &str, body: &str) -> HttpRequest {
+        http::Request::builder()
+            .method(method)
+            .uri(url)
+            .header("X-goog-api-key", "secret-key")
+            .body(body.as_bytes().to_vec())
+            .unwrap()
+    }
+
+    fn recorded(method: &str, url: &str, body: &str) -> RecordedRequest {
+        RecordedRequest::new(&request(method, url, body))
+    }
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cassette-{}-{}.json", name, rand::random::<u64>()))
+    }
+
+    #[test]
+    fn json_bodies_match_as_values() {
+        let rules = MatchRules::new();
+        let a = recorded("POST", "https://example.com/v2", r#"{"q": ["hello"], "target": "ja"}"#);
+        let b = recorded("POST", "https://example.com/v2", r#"{"target":"ja","q":["hello"]}"#);
+        let c = recorded("POST", "https://example.com/v2", r#"{"target":"fr","q":["hello"]}"#);
+        assert!(rules.matches(&a, &b));
+        assert!(!rules.matches(&a, &c));
+        assert!(rules.with_body(false).matches(&a, &c));
+    }
+
+    #[test]
+    fn method_and_url_match_with_secrets_scrubbed() {
+        let rules = MatchRules::new();
+        let a = recorded("GET", "https://example.com/v2/languages?key=first&target=en", "");
+        let b = recorded("GET", "https://example.com/v2/languages?key=second&target=en", "");
+        let c = recorded("GET", "https://example.com/v2/languages?key=first&target=ja", "");
+        let d = recorded("POST", "https://example.com/v2/languages?key=first&target=en", "");
+        assert!(rules.matches(&a, &b));
+        assert!(!rules.matches(&a, &c));
+        assert!(rules.with_url(false).matches(&a, &c));
+        assert!(!rules.matches(&a, &d));
+        assert!(rules.with_method(false).matches(&a, &d));
+    }
+
+    #[tokio::test]
+    async fn record_then_replay() {
+        let path = cassette_path("record");
+        let recorder = CassetteTransport::record(&path, EchoTransport);
+        recorder.send(request("POST", "https://example.com/v2?key=secret-key", r#"{"n":1}"#)).await.unwrap();
+        recorder.send(request("POST", "https://example.com/v2?key=secret-key", r#"{"n":2}"#)).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("secret-key"));
+
+        let player = CassetteTransport::replay(&path).unwrap();
+        let response = player.send(request("POST", "https://example.com/v2?key=other-key", r#"{"n": 2}"#)).await.unwrap();
+        assert_eq!((response.status().as_u16(), response.body().as_slice()), (200, br#"{"n":2}"#.as_slice()));
+        assert_eq!(response.headers()["x-echo"], "1");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn each_interaction_is_replayed_once_in_order() {
+        let path = cassette_path("replay");
+        let recorder = CassetteTransport::record(&path, EchoTransport);
+        for body in [r#"{"n":1}"#, r#"{"n":2}"#] {
+            recorder.send(request("POST", "https://example.com/v2", body)).await.unwrap();
+        }
+
+        let player = CassetteTransport::replay(&path).unwrap().with_match_rules(MatchRules::new().with_body(false));
+        let first = player.send(request("POST", "https://example.com/v2", "")).await.unwrap();
+        let second = player.send(request("POST", "https://example.com/v2", "")).await.unwrap();
+        assert_eq!(first.body().as_slice(), br#"{"n":1}"#);
+        assert_eq!(second.body().as_slice(), br#"{"n":2}"#);
+        assert!(player.send(request("POST", "https://example.com/v2", "")).await.is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use reqwest::{Client, Proxy};
+
+use super::mtls::ClientCertificate;
+
+static DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+
+/// Configuration for the HTTP client shared by a service and its token requests.
+///
+/// Build a `reqwest::Client` once with `build` and pass it to the services with `with_http_client`,
+/// or pass the config directly with `with_client_config`.
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    pool_idle_timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    proxy: Option<String>,
+    user_agent: Option<String>,
+    http2_prior_knowledge: bool,
+    client_certificate: Option<ClientCertificate>,
+}
+
+impl ClientConfig {
+    /// Create `ClientConfig` with reqwest defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Timeout for the whole request, from connecting until the response body has been read.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for the connect phase only.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// How long idle connections are kept in the pool.
+    pub fn with_pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum number of idle connections kept per host.
+    pub fn with_pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max_idle);
+        self
+    }
+
+    /// Proxy all requests through the given proxy URL, for example `http://proxy.example.com:8080`.
+    pub fn with_proxy(mut self, proxy_url: &str) -> Self {
+        self.proxy = Some(proxy_url.to_owned());
+        self
+    }
+
+    /// `User-Agent` header sent with every request. Defaults to `google-api-rust-client-unoffical/<version>`.
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_owned());
+        self
+    }
+
+    /// Use HTTP/2 without negotiating it first.
+    pub fn with_http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Present a client certificate for mutual TLS. Services then switch to the `*.mtls.googleapis.com` endpoints,
+    /// unless `GOOGLE_API_USE_MTLS_ENDPOINT` is `never`.
+    /// Without it, the SecureConnect certificate is used if `GOOGLE_API_USE_CLIENT_CERTIFICATE` is `true`.
+    pub fn with_client_certificate(mut self, client_certificate: ClientCertificate) -> Self {
+        self.client_certificate = Some(client_certificate);
+        self
+    }
+
+    /// Build a `reqwest::Client` from the config.
+    pub fn build(&self) -> Result<Client> {
+        self.build_with_certificate(self.client_certificate()?.as_ref())
+    }
+
+    /// The certificate set with `with_client_certificate`, or the default one of `ClientCertificate::from_env`.
+    pub(super) fn client_certificate(&self) -> Result<Option<ClientCertificate>> {
+        match &self.client_certificate {
+            Some(client_certificate) => Ok(Some(client_certificate.clone())),
+            None => ClientCertificate::from_env(),
+        }
+    }
+
+    pub(super) fn build_with_certificate(&self, client_certificate: Option<&ClientCertificate>) -> Result<Client> {
+        let mut builder = Client::builder()
+            .user_agent(self.user_agent.clone().unwrap_or(DEFAULT_USER_AGENT.to_owned()));
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max_idle) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(client_certificate) = client_certificate {
+            builder = client_certificate.add_to(builder)?;
+        }
+
+        Ok(builder.build()?)
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use anyhow::Result;
+use tokio::sync::Mutex;
+use tower::util::BoxService;
+use tower::{Layer, Service, ServiceExt};
+
+use super::http_transport::HttpTransport;
+
+/// Request sent by the services, with a fully buffered body.
+pub type HttpRequest = http::Request<Vec<u8>>;
+/// Response returned to the services, with a fully buffered body.
+pub type HttpResponse = http::Response<Vec<u8>>;
+pub type BoxError = tower::BoxError;
+/// Type-erased `tower::Service` that sends a request.
+pub type HttpService = BoxService<HttpRequest, HttpResponse, BoxError>;
+
+
+/// `tower::Service` sending requests with an `HttpTransport`. The innermost service of every stack.
+#[derive(Debug, Clone)]
+pub struct TransportService {
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl TransportService {
+    pub fn new(transport: Arc<dyn HttpTransport>) -> Self {
+        Self { transport }
+    }
+}
+
+impl Service<HttpRequest> for TransportService {
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<HttpResponse, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let transport = self.transport.clone();
+        Box::pin(async move { transport.send(request).await })
+    }
+}
+
+
+type BoxLayer = Arc<dyn Fn(HttpService) -> HttpService + Send + Sync>;
+
+/// The layers added by the user, and the stack built from them on top of a `TransportService`.
+///
+/// A single instance of the stack serves every request, so layers keeping state per instance,
+/// such as `RateLimit` and `ConcurrencyLimit`, apply across requests and clones of the service.
+#[derive(Clone)]
+pub(crate) struct HttpStack {
+    transport: Arc<dyn HttpTransport>,
+    layers: Vec<BoxLayer>,
+    service: Arc<Mutex<HttpService>>,
+}
+
+impl fmt::Debug for HttpStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpStack")
+            .field("transport", &self.transport)
+            .field("layers", &self.layers.len())
+            .finish()
+    }
+}
+
+impl HttpStack {
+    pub(crate) fn new(transport: Arc<dyn HttpTransport>) -> Self {
+        let service = BoxService::new(TransportService::new(transport.clone()));
+        Self { transport, layers: vec![], service: Arc::new(Mutex::new(service)) }
+    }
+
+    /// Add a layer on top of the stack. Layers added last see requests first.
+    pub(crate) fn with_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<HttpService> + Send + Sync + 'static,
+        L::Service: Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Send + 'static,
+        <L::Service as Service<HttpRequest>>::Future: Send + 'static,
+    {
+        self.layers.push(Arc::new(move |service| BoxService::new(layer.layer(service))));
+        self.rebuild()
+    }
+
+    /// Build the stack again on top of a new transport, keeping the layers.
+    pub(crate) fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self.rebuild()
+    }
+
+    fn rebuild(mut self) -> Self {
+        let inner = BoxService::new(TransportService::new(self.transport.clone()));
+        let service = self.layers.iter().fold(inner, |service, layer| layer(service));
+        self.service = Arc::new(Mutex::new(service));
+        self
+    }
+
+    /// Wait until the stack is ready and send the request. The stack is locked until the request is handed to it,
+    /// not while the response is awaited, so a layer that is not ready holds up the other requests of the service.
+    pub(crate) async fn send(&self, request: HttpRequest) -> Result<HttpResponse, BoxError> {
+        let response = {
+            let mut service = self.service.lock().await;
+            service.ready().await?.call(request)
+        };
+        response.await
+    }
+}
+
+
+pub(crate) fn clone_request(request: &HttpRequest) -> HttpRequest {
+    let mut cloned = http::Request::new(request.body().clone());
+    *cloned.method_mut() = request.method().clone();
+    *cloned.uri_mut() = request.uri().clone();
+    *cloned.version_mut() = request.version();
+    *cloned.headers_mut() = request.headers().clone();
+    cloned
+}
+
+
+static REDACTED: &str = "[REDACTED]";
+static SENSITIVE_HEADERS: [&str; 5] = ["authorization", "proxy-authorization", "x-goog-api-key", "cookie", "set-cookie"];
+static SENSITIVE_QUERY_PARAMS: [&str; 3] = ["key", "access_token", "token"];
+
+/// Scheme, host and path of the URI, without the query.
+pub(crate) fn endpoint(uri: &http::Uri) -> String {
+    match (uri.scheme_str(), uri.authority()) {
+        (Some(scheme), Some(authority)) => format!("{}://{}{}", scheme, authority, uri.path()),
+        _ => uri.path().to_owned(),
+    }
+}
+
+/// The URI with the values of API key and token query parameters replaced, for logging.
+pub(crate) fn redacted_uri(uri: &http::Uri) -> String {
+    let Some(query) = uri.query() else {
+        return uri.to_string();
+    };
+    let redacted_query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if SENSITIVE_QUERY_PARAMS.contains(&name) => format!("{}={}", name, REDACTED),
+            _ => pair.to_owned(),
+        })
+        .collect();
+    format!("{}?{}", endpoint(uri), redacted_query.join("&"))
+}
+
+/// The headers with the values of authorization and API key headers replaced, for logging.
+pub(crate) fn redacted_headers(headers: &http::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_owned()
+            } else {
+                value.to_str().unwrap_or_default().to_owned()
+            };
+            (name.as_str().to_owned(), value)
+        })
+        .collect()
+}
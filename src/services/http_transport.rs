@@ -0,0 +1,49 @@
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use super::http_service::{BoxError, HttpRequest, HttpResponse};
+
+
+/// Sends a request and returns the status, headers and body of the response.
+///
+/// Implement this to send requests with another HTTP stack, such as hyper directly,
+/// or to answer requests in memory in tests. `ReqwestTransport` is used by default.
+#[async_trait]
+pub trait HttpTransport: Debug + Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, BoxError>;
+}
+
+
+/// `HttpTransport` sending requests with a `reqwest::Client`.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    /// Create `ReqwestTransport`.
+    ///
+    /// * `client` -  `reqwest::Client` to send requests with.
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, BoxError> {
+        let request = reqwest::Request::try_from(request)?;
+        let response = self.client.execute(request).await?;
+
+        let mut builder = http::Response::builder()
+            .status(response.status())
+            .version(response.version());
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(response.headers().clone());
+        }
+        let body = response.bytes().await?.to_vec();
+        Ok(builder.body(body)?)
+    }
+}
@@ -1,43 +1,213 @@
+#[cfg(feature = "translate")]
 pub mod translate_service;
+#[cfg(feature = "routes")]
 pub mod route_service;
 pub mod service_error;
+pub mod client_config;
+pub mod retry_policy;
+pub mod http_service;
+pub mod http_transport;
+pub mod rate_limiter;
+pub mod cassette;
+pub mod operation;
+pub mod pagination;
+pub mod request_options;
+pub mod response_cache;
+pub mod mtls;
 
-use anyhow::{bail, Result};
-use reqwest::{header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE}, RequestBuilder};
-use service_error::ServiceErrorResponse;
+use std::env;
+use std::time::Instant;
 
-use crate::auth::service_account::ServiceAccountCredentials;
+use anyhow::{anyhow, bail, Result};
+use std::sync::Arc;
+
+use reqwest::{header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE}, Client, Method, Url};
+use http_service::{BoxError, HttpRequest, HttpResponse, HttpService, HttpStack};
+use http_transport::{HttpTransport, ReqwestTransport};
+use rate_limiter::RateLimiter;
+use response_cache::ResponseCache;
+use client_config::ClientConfig;
+use mtls::ClientCertificate;
+use retry_policy::RetryPolicy;
+use service_error::ServiceError;
+use sha2::{Digest, Sha256};
+use tokio::time;
+use tracing::Span;
+
+use crate::auth::service_account::{self, ServiceAccountCredentials};
+use crate::auth::watched_credentials::WatchedServiceAccountCredentials;
+use crate::auth::delegated_credentials_pool::DelegatedCredentials;
+use crate::metrics;
+
+
+/// Describes an API method for retries and metrics.
+#[derive(Debug, Clone, Copy)]
+struct ApiMethod {
+    service: &'static str,
+    name: &'static str,
+    idempotent: bool,
+}
 
 
 #[derive(Debug, Clone)]
 struct ServiceBase {
     api_key: Option<String>,
     service_account_credentials: Option<ServiceAccountCredentials>,
+    watched_credentials: Option<WatchedServiceAccountCredentials>,
+    delegated_credentials: Option<DelegatedCredentials>,
+    retry_policy: RetryPolicy,
+    endpoint: Option<Url>,
+    http_stack: HttpStack,
+    rate_limiter: Option<RateLimiter>,
+    response_cache: Option<ResponseCache>,
+    client_certificate: Option<ClientCertificate>,
 }
 
 impl ServiceBase {
     fn new_with_api_key(api_key: String) -> Self {
-        return Self { api_key: Some(api_key), service_account_credentials: None }
+        let (_, transport, client_certificate) = Self::default_transport();
+        Self { api_key: Some(api_key), ..Self::unauthenticated(transport, client_certificate) }
     }
 
+    // credentials send their token requests with the client of the service, unless they have their own
     fn new_with_credentials(service_account_credentials: ServiceAccountCredentials, scopes: Vec<&str>) -> Self {
-        let scoped_credentials = service_account_credentials.with_scopes(scopes);
-        return Self { api_key: None, service_account_credentials: Some(scoped_credentials) }
+        let (client, transport, client_certificate) = Self::default_transport();
+        let scoped_credentials = service_account_credentials.with_scopes(scopes).with_default_http_client(client.clone());
+        Self { service_account_credentials: Some(scoped_credentials), ..Self::unauthenticated(transport, client_certificate) }
+    }
+
+    fn new_with_watched_credentials(watched_credentials: WatchedServiceAccountCredentials, scopes: Vec<&str>) -> Self {
+        let (client, transport, client_certificate) = Self::default_transport();
+        let scoped_credentials = watched_credentials.with_scopes(scopes).with_default_http_client(client.clone());
+        Self { watched_credentials: Some(scoped_credentials), ..Self::unauthenticated(transport, client_certificate) }
+    }
+
+    fn new_with_delegated_credentials(delegated_credentials: DelegatedCredentials, scopes: Vec<&str>) -> Self {
+        let (client, transport, client_certificate) = Self::default_transport();
+        let scoped_credentials = delegated_credentials.with_scopes(scopes).with_default_http_client(client.clone());
+        Self { delegated_credentials: Some(scoped_credentials), ..Self::unauthenticated(transport, client_certificate) }
+    }
+
+    // services created without a `ClientConfig` share the default client; if the client certificate asked for with
+    // `GOOGLE_API_USE_CLIENT_CERTIFICATE` cannot be used, their requests fail instead of being sent without it
+    fn default_transport() -> (Client, Arc<dyn HttpTransport>, Option<ClientCertificate>) {
+        match mtls::default_client() {
+            Ok((client, client_certificate)) => (client.clone(), Arc::new(ReqwestTransport::new(client)), client_certificate),
+            Err(error) => (service_account::default_http_client(), Arc::new(mtls::ClientCertificateErrorTransport::new(error)), None),
+        }
+    }
+
+    fn unauthenticated(transport: Arc<dyn HttpTransport>, client_certificate: Option<ClientCertificate>) -> Self {
+        Self {
+            api_key: None,
+            service_account_credentials: None,
+            watched_credentials: None,
+            delegated_credentials: None,
+            http_stack: HttpStack::new(transport),
+            retry_policy: RetryPolicy::none(),
+            endpoint: None,
+            rate_limiter: None,
+            response_cache: None,
+            client_certificate,
+        }
     }
 }
 
 
 impl ServiceBase {
 
+    fn with_http_client(mut self, client: Client) -> Self {
+        self.service_account_credentials = self.service_account_credentials.map(|credentials| credentials.with_http_client(client.clone()));
+        self.watched_credentials = self.watched_credentials.map(|credentials| credentials.with_http_client(client.clone()));
+        self.delegated_credentials = self.delegated_credentials.map(|credentials| credentials.with_http_client(client.clone()));
+        self.http_stack = self.http_stack.with_transport(Arc::new(ReqwestTransport::new(client)));
+        self.client_certificate = None;
+        self
+    }
+
+    fn with_client_config(self, config: &ClientConfig) -> Result<Self> {
+        let client_certificate = config.client_certificate()?;
+        let mut base = self.with_http_client(config.build_with_certificate(client_certificate.as_ref())?);
+        base.client_certificate = client_certificate;
+        Ok(base)
+    }
+
+    fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.http_stack = self.http_stack.with_transport(transport);
+        self
+    }
+
+    fn with_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<HttpService> + Send + Sync + 'static,
+        L::Service: tower::Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Send + 'static,
+        <L::Service as tower::Service<HttpRequest>>::Future: Send + 'static,
+    {
+        self.http_stack = self.http_stack.with_layer(layer);
+        self
+    }
+
+    fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    fn with_response_cache(mut self, response_cache: ResponseCache) -> Self {
+        self.response_cache = Some(response_cache);
+        self
+    }
+
+    /// Wait until the rate limiter, if any, allows sending the characters.
+    async fn acquire_characters(&self, characters: usize) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire_characters(characters).await;
+        }
+    }
+
+    fn with_endpoint(mut self, endpoint: Url) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// Build the URL of an API method.
+    /// The endpoint set with `with_endpoint` takes precedence over the one in the environment variable, which takes precedence over the default.
+    /// The default switches to its mTLS variant according to `GOOGLE_API_USE_MTLS_ENDPOINT` and the client certificate.
+    fn endpoint_url(&self, default_endpoint: &str, endpoint_env_var: &str, path: &str) -> Result<Url> {
+        let mut endpoint = match &self.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => match env::var(endpoint_env_var) {
+                Ok(endpoint) => Url::parse(&endpoint)?,
+                Err(_) => Url::parse(&mtls::default_endpoint(default_endpoint, self.client_certificate.is_some()))?,
+            },
+        };
+
+        // keep any path prefix of the endpoint, such as a proxy route
+        if !endpoint.path().ends_with('/') {
+            endpoint.set_path(&format!("{}/", endpoint.path()));
+        }
+        Ok(endpoint.join(path)?)
+    }
+
     async fn create_headers(&mut self) -> Result<HeaderMap>{
         let mut headers = HeaderMap::new();
 
         if let Some(api_key) = &self.api_key {
-            headers.insert("X-goog-api-key", HeaderValue::from_str(&api_key)?);
+            headers.insert("X-goog-api-key", HeaderValue::from_str(api_key)?);
         } else if let Some(mut credentials) = self.service_account_credentials.to_owned() {
             let token = credentials.get_access_token().await?;
             self.service_account_credentials = Some(credentials);
             headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token))?);
+        } else if let Some(credentials) = self.watched_credentials.as_mut() {
+            let token = credentials.get_access_token().await?;
+            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token))?);
+        } else if let Some(credentials) = &self.delegated_credentials {
+            let token = credentials.get_access_token().await?;
+            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token))?);
         } else {
             bail!("Unknown Auth Method!")
         };
@@ -46,16 +216,124 @@ impl ServiceBase {
         Ok(headers)
     }
 
-    async fn make_request(&mut self, request: RequestBuilder) -> Result<String> {
-        let response = request.send().await?;
-        let status_code = response.status();
-        let body: String = response.text().await?;
+    /// Add the authentication headers to the headers of a request, keeping the headers already set,
+    /// such as the extra headers of `RequestOptions`.
+    async fn add_auth_headers(&mut self, headers: &mut HeaderMap) -> Result<()> {
+        for (name, value) in self.create_headers().await?.iter() {
+            if !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Caller the requests are authenticated as: a hash of the API key, or the service account and subject.
+    fn caller_identity(&self) -> String {
+        if let Some(api_key) = &self.api_key {
+            let digest = Sha256::digest(api_key.as_bytes());
+            format!("key:{}", digest.iter().take(16).map(|byte| format!("{:02x}", byte)).collect::<String>())
+        } else if let Some(credentials) = &self.service_account_credentials {
+            format!("sa:{}", credentials.identity())
+        } else if let Some(credentials) = &self.watched_credentials {
+            format!("sa:{}", credentials.identity())
+        } else if let Some(credentials) = &self.delegated_credentials {
+            format!("sa:{}", credentials.identity())
+        } else {
+            String::new()
+        }
+    }
 
-        if !status_code.is_success() {
-            let error_response: ServiceErrorResponse = serde_json::from_str(&body).unwrap_or_default();
-            bail!(format!("Response Error! Code: {}, Message: {}", error_response.error.code, error_response.error.message));
+    fn build_request(&self, method: Method, url: Url, headers: HeaderMap, body: Vec<u8>) -> Result<HttpRequest> {
+        let mut builder = http::Request::builder().method(method).uri(url.as_str());
+        if let Some(request_headers) = builder.headers_mut() {
+            request_headers.extend(headers);
         }
+        Ok(builder.body(body)?)
+    }
+
+    /// Send the request, retrying transient failures according to the retry policy.
+    /// Requests that are not idempotent are only retried if the policy allows it.
+    /// Each attempt waits for the rate limiter, if any.
+    ///
+    /// The endpoint, status, latency, retry count and response size are recorded on the current span,
+    /// which the service methods create with `tracing::instrument`.
+    async fn make_request(&mut self, request: HttpRequest, method: &ApiMethod) -> Result<String> {
+        let max_attempts = if method.idempotent || self.retry_policy.retries_non_idempotent() {
+            self.retry_policy.max_attempts()
+        } else {
+            1
+        };
+
+        let span = Span::current();
+        span.record("endpoint", http_service::endpoint(request.uri()).as_str());
+        let started = Instant::now();
+
+        let mut request = request;
+        let mut attempt: u32 = 1;
+        loop {
+            let next_request = if attempt < max_attempts { Some(http_service::clone_request(&request)) } else { None };
 
-        Ok(body)
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire_query().await;
+            }
+
+            tracing::debug!(
+                attempt,
+                method = %request.method(),
+                url = %http_service::redacted_uri(request.uri()),
+                headers = ?http_service::redacted_headers(request.headers()),
+                "sending request"
+            );
+
+            let response = match self.http_stack.send(request).await {
+                Ok(response) => response,
+                Err(error) => {
+                    let Some(next_request) = next_request.filter(|_| retry_policy::is_retryable_error(error.as_ref())) else {
+                        span.record("retry_count", attempt - 1);
+                        span.record("latency_ms", started.elapsed().as_millis() as u64);
+                        metrics::record_request(method.service, method.name, 0, started.elapsed());
+                        tracing::debug!(error = %error, "request failed");
+                        return Err(anyhow!(error));
+                    };
+                    let delay = self.retry_policy.backoff(attempt);
+                    tracing::debug!(error = %error, delay_ms = delay.as_millis() as u64, "retrying request");
+                    time::sleep(delay).await;
+                    request = next_request;
+                    attempt += 1;
+                    continue;
+                }
+            };
+
+            let status_code = response.status();
+            let retry_after = retry_policy::retry_after(response.headers());
+            let body = String::from_utf8_lossy(response.body()).into_owned();
+
+            span.record("status", status_code.as_u16());
+            span.record("retry_count", attempt - 1);
+            span.record("latency_ms", started.elapsed().as_millis() as u64);
+            span.record("response_size", body.len());
+
+            if status_code.is_success() {
+                metrics::record_request(method.service, method.name, status_code.as_u16(), started.elapsed());
+                return Ok(body);
+            }
+
+            let error = ServiceError::from_response(status_code.as_u16(), &body);
+            let requested_delay = retry_after.or(retry_policy::retry_info_delay(&error));
+            // give up rather than wait longer than the policy allows
+            let retry = next_request
+                .filter(|_| retry_policy::is_retryable_status(status_code))
+                .filter(|_| requested_delay.is_none_or(|delay| delay <= self.retry_policy.max_backoff()));
+            let Some(next_request) = retry else {
+                metrics::record_request(method.service, method.name, status_code.as_u16(), started.elapsed());
+                return Err(error.into());
+            };
+
+            let delay = requested_delay.unwrap_or(self.retry_policy.backoff(attempt));
+            tracing::debug!(status = status_code.as_u16(), delay_ms = delay.as_millis() as u64, "retrying request");
+            time::sleep(delay).await;
+            request = next_request;
+            attempt += 1;
+        }
     }
 }
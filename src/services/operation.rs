@@ -0,0 +1,434 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use reqwest::{Method, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::time::{self, Instant};
+
+use super::{ApiMethod, ServiceBase};
+use super::service_error::ServiceError;
+
+
+/// How `Operation::wait` polls an operation until it is done.
+///
+/// The delay between polls grows from `initial_delay` by `multiplier` up to `max_delay`.
+/// Without a deadline, `wait` polls until the operation is done.
+#[derive(Debug, Clone)]
+pub struct PollingPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    deadline: Option<Duration>,
+}
+
+impl Default for PollingPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 1.5,
+            deadline: None,
+        }
+    }
+}
+
+impl PollingPolicy {
+    /// Create `PollingPolicy` with delays starting at 1 second and growing by 1.5 up to 60 seconds, without a deadline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delay before the first poll.
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Upper bound of the delay between polls.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Factor the delay grows by after each poll.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Give up waiting after this long. The operation keeps running on the server.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    fn next_delay(&self, delay: Duration) -> Duration {
+        delay.mul_f64(self.multiplier).min(self.max_delay)
+    }
+}
+
+
+/// `google.longrunning.Operation` as returned by the REST APIs.
+#[derive(Debug, Clone, Deserialize)]
+struct OperationResource {
+    name: String,
+    #[serde(default)]
+    metadata: Option<Value>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    error: Option<OperationError>,
+    #[serde(default)]
+    response: Option<Value>,
+}
+
+/// `google.rpc.Status` of a failed operation.
+#[derive(Debug, Clone, Deserialize)]
+struct OperationError {
+    #[serde(default)]
+    code: i32,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    details: Vec<Value>,
+}
+
+
+/// Long-running operation started by a Google API, such as a Cloud Translation batch translation.
+///
+/// `T` is the type of the response of the operation once it is done, and `M` the type of its metadata,
+/// which usually reports progress. Use `poll` to refresh the operation, or `wait` to poll it until it is done
+/// according to the `PollingPolicy`. Operations use the credentials, retry policy and transport of the service
+/// that returned them.
+pub struct Operation<T, M = Value> {
+    base: ServiceBase,
+    root: Url,
+    service: &'static str,
+    operation: OperationResource,
+    polling_policy: PollingPolicy,
+    _types: PhantomData<fn() -> (T, M)>,
+}
+
+impl<T, M> Clone for Operation<T, M> {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            root: self.root.clone(),
+            service: self.service,
+            operation: self.operation.clone(),
+            polling_policy: self.polling_policy.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<T, M> fmt::Debug for Operation<T, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Operation")
+            .field("name", &self.operation.name)
+            .field("done", &self.operation.done)
+            .field("metadata", &self.operation.metadata)
+            .finish()
+    }
+}
+
+impl<T: DeserializeOwned, M: DeserializeOwned> Operation<T, M> {
+    /// Create `Operation` from the JSON returned by a service.
+    ///
+    /// * `base` -  `ServiceBase` of the service that returned the operation.
+    /// * `root` -  URL the operation name is resolved against, such as `https://translation.googleapis.com/v3/`.
+    /// * `service` -  Name of the service, for metrics.
+    /// * `operation` -  JSON of the `google.longrunning.Operation`.
+    // only the Cloud Translation API starts operations so far
+    #[cfg_attr(not(feature = "translate"), allow(dead_code))]
+    pub(super) fn new(base: ServiceBase, root: Url, service: &'static str, operation: Value) -> Result<Self> {
+        Ok(Self {
+            base,
+            root,
+            service,
+            operation: serde_json::from_value(operation)?,
+            polling_policy: PollingPolicy::default(),
+            _types: PhantomData,
+        })
+    }
+
+    /// Set how `wait` polls the operation.
+    ///
+    /// * `polling_policy` -  `PollingPolicy` to use.
+    pub fn with_polling_policy(mut self, polling_policy: PollingPolicy) -> Self {
+        self.polling_policy = polling_policy;
+        self
+    }
+
+    /// Name of the operation, such as `projects/my-project/locations/us-central1/operations/1234`.
+    pub fn name(&self) -> &str {
+        &self.operation.name
+    }
+
+    /// Whether the operation is done, successfully or not.
+    pub fn done(&self) -> bool {
+        self.operation.done
+    }
+
+    /// Metadata of the operation as of the last poll, if the service sent any.
+    pub fn metadata(&self) -> Result<Option<M>> {
+        match &self.operation.metadata {
+            Some(metadata) => Ok(Some(serde_json::from_value(metadata.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Result of the operation, or `None` if it is not done yet.
+    /// A failed operation returns its error as a `ServiceError`.
+    /// An operation done without a response, such as one returning `google.protobuf.Empty`, only succeeds
+    /// if `T` can be deserialized from `null`, like `()` or `Option<_>`.
+    pub fn result(&self) -> Option<Result<T>> {
+        if !self.operation.done {
+            return None;
+        }
+        Some(match (&self.operation.error, &self.operation.response) {
+            (Some(error), _) => Err(ServiceError::from_rpc_status(error.code, &error.message, error.details.clone()).into()),
+            (None, Some(response)) => serde_json::from_value(response.clone()).map_err(Into::into),
+            (None, None) => serde_json::from_value(Value::Null)
+                .map_err(|_| anyhow!("Operation {} is done without a response or an error!", self.operation.name)),
+        })
+    }
+
+    /// Refresh the operation from the server.
+    #[tracing::instrument(skip_all, fields(method = "get_operation", endpoint, status, latency_ms, retry_count, response_size))]
+    pub async fn poll(&mut self) -> Result<()> {
+        let url = self.root.join(&self.operation.name)?;
+        let headers = self.base.create_headers().await?;
+        let request = self.base.build_request(Method::GET, url, headers, vec![])?;
+
+        let method = ApiMethod { service: self.service, name: "get_operation", idempotent: true };
+        let body = self.base.make_request(request, &method).await?;
+        self.operation = serde_json::from_str(&body)?;
+        Ok(())
+    }
+
+    /// Poll the operation until it is done and return its result.
+    /// Fails if the deadline of the `PollingPolicy` passes first, including in the middle of a poll.
+    pub async fn wait(&mut self) -> Result<T> {
+        let started = Instant::now();
+        let mut delay = self.polling_policy.initial_delay;
+        loop {
+            if let Some(result) = self.result() {
+                return result;
+            }
+
+            if let Some(remaining) = self.remaining(started)? {
+                delay = delay.min(remaining);
+            }
+            tracing::debug!(operation = %self.operation.name, delay_ms = delay.as_millis() as u64, "waiting for operation");
+            time::sleep(delay).await;
+
+            // a poll can itself retry for longer than the deadline
+            match self.remaining(started)? {
+                Some(remaining) => match time::timeout(remaining, self.poll()).await {
+                    Ok(result) => result?,
+                    Err(_) => bail!("Operation {} not done after {:?}!", self.operation.name, started.elapsed()),
+                },
+                None => self.poll().await?,
+            }
+            delay = self.polling_policy.next_delay(delay);
+        }
+    }
+
+    // time left before the deadline of the polling policy, if any
+    fn remaining(&self, started: Instant) -> Result<Option<Duration>> {
+        let Some(deadline) = self.polling_policy.deadline else {
+            return Ok(None);
+        };
+        let remaining = deadline.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            bail!("Operation {} not done after {:?}!", self.operation.name, deadline);
+        }
+        Ok(Some(remaining))
+    }
+
+    /// Ask the server to cancel the operation. Cancellation is best effort:
+    /// poll the operation to know whether it was cancelled or completed anyway.
+    #[tracing::instrument(skip_all, fields(method = "cancel_operation", endpoint, status, latency_ms, retry_count, response_size))]
+    pub async fn cancel(&mut self) -> Result<()> {
+        let url = self.root.join(&format!("{}:cancel", self.operation.name))?;
+        let headers = self.base.create_headers().await?;
+        let request = self.base.build_request(Method::POST, url, headers, b"{}".to_vec())?;
+
+        let method = ApiMethod { service: self.service, name: "cancel_operation", idempotent: true };
+        self.base.make_request(request, &method).await?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    use super::*;
+    use crate::services::http_service::{BoxError, HttpRequest, HttpResponse};
+    use crate::services::http_transport::HttpTransport;
+
+    static ROOT: &str = "https://translation.googleapis.com/v3/";
+    static NAME: &str = "projects/test-project/locations/us-central1/operations/1234";
+
+    /// Answers polls with the queued operations, repeating the last one, after `latency`.
+    #[derive(Debug)]
+    struct OperationServer {
+        operations: Mutex<VecDeque<Value>>,
+        latency: Duration,
+        started: Instant,
+        // method, URL, body and time since the server started of each request
+        requests: Mutex<Vec<(String, String, String, Duration)>>,
+    }
+
+    impl OperationServer {
+        fn new(operations: Vec<Value>, latency: Duration) -> Arc<Self> {
+            Arc::new(Self { operations: Mutex::new(operations.into()), latency, started: Instant::now(), requests: Mutex::default() })
+        }
+
+        fn requests(&self) -> Vec<(String, String, String, Duration)> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl HttpTransport for Arc<OperationServer> {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, BoxError> {
+            let body = String::from_utf8(request.body().clone())?;
+            self.requests.lock().unwrap().push((request.method().to_string(), request.uri().to_string(), body, self.started.elapsed()));
+            time::sleep(self.latency).await;
+
+            let mut operations = self.operations.lock().unwrap();
+            let operation = if operations.len() > 1 { operations.pop_front() } else { operations.front().cloned() };
+            Ok(http::Response::builder().status(200).body(operation.unwrap_or(json!({})).to_string().into_bytes())?)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Translated {
+        count: u32,
+    }
+
+    fn pending() -> Value {
+        json!({ "name": NAME, "metadata": { "count": 1 } })
+    }
+
+    fn operation<T: DeserializeOwned>(operation: Value, server: &Arc<OperationServer>) -> Result<Operation<T, Translated>> {
+        let base = ServiceBase::new_with_api_key("test-api-key".to_owned()).with_transport(Arc::new(server.clone()));
+        Operation::new(base, Url::parse(ROOT)?, "translate", operation)
+    }
+
+    #[tokio::test]
+    async fn poll_refreshes_the_operation() -> Result<()> {
+        let server = OperationServer::new(vec![json!({ "name": NAME, "metadata": { "count": 2 }, "done": true, "response": { "count": 3 } })], Duration::ZERO);
+        let mut operation = operation::<Translated>(pending(), &server)?;
+        assert_eq!((operation.name(), operation.done()), (NAME, false));
+        assert_eq!(operation.metadata()?, Some(Translated { count: 1 }));
+        assert!(operation.result().is_none());
+
+        operation.poll().await?;
+
+        assert!(operation.done());
+        assert_eq!(operation.metadata()?, Some(Translated { count: 2 }));
+        assert_eq!(operation.result().unwrap()?, Translated { count: 3 });
+        let (method, url, _, _) = &server.requests()[0];
+        assert_eq!((method.as_str(), url.as_str()), ("GET", format!("{}{}", ROOT, NAME).as_str()));
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_backs_off_until_done() -> Result<()> {
+        let done = json!({ "name": NAME, "done": true, "response": { "count": 3 } });
+        let server = OperationServer::new(vec![pending(), pending(), pending(), done], Duration::ZERO);
+        let polling_policy = PollingPolicy::new().with_initial_delay(Duration::from_secs(1)).with_multiplier(2.0).with_max_delay(Duration::from_secs(3));
+        let mut operation = operation::<Translated>(pending(), &server)?.with_polling_policy(polling_policy);
+
+        assert_eq!(operation.wait().await?, Translated { count: 3 });
+
+        let polled_at: Vec<u64> = server.requests().iter().map(|(_, _, _, elapsed)| elapsed.as_secs()).collect();
+        assert_eq!(polled_at, [1, 3, 6, 9]);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_fails_after_the_deadline() -> Result<()> {
+        let server = OperationServer::new(vec![pending()], Duration::ZERO);
+        let polling_policy = PollingPolicy::new().with_initial_delay(Duration::from_secs(1)).with_multiplier(2.0).with_deadline(Duration::from_secs(5));
+        let mut operation = operation::<Translated>(pending(), &server)?.with_polling_policy(polling_policy);
+
+        let started = Instant::now();
+        let error = operation.wait().await.unwrap_err();
+
+        assert_eq!(error.to_string(), format!("Operation {} not done after 5s!", NAME));
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+        assert_eq!(server.requests().len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_deadline_interrupts_a_poll() -> Result<()> {
+        let server = OperationServer::new(vec![pending()], Duration::from_secs(60));
+        let polling_policy = PollingPolicy::new().with_initial_delay(Duration::from_secs(1)).with_deadline(Duration::from_secs(2));
+        let mut operation = operation::<Translated>(pending(), &server)?.with_polling_policy(polling_policy);
+
+        let started = Instant::now();
+        assert!(operation.wait().await.is_err());
+
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+        assert_eq!(server.requests().len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cancel_posts_to_the_cancel_method() -> Result<()> {
+        let server = OperationServer::new(vec![json!({})], Duration::ZERO);
+        let mut operation = operation::<Translated>(pending(), &server)?;
+
+        operation.cancel().await?;
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let (method, url, body, _) = &requests[0];
+        assert_eq!((method.as_str(), url.as_str(), body.as_str()), ("POST", format!("{}{}:cancel", ROOT, NAME).as_str(), "{}"));
+        Ok(())
+    }
+
+    #[test]
+    fn result_of_a_failed_operation_is_its_status() -> Result<()> {
+        let server = OperationServer::new(vec![], Duration::ZERO);
+        let details = json!([{ "@type": "type.googleapis.com/google.rpc.ErrorInfo", "reason": "BUCKET_NOT_FOUND", "domain": "translate.googleapis.com" }]);
+        let failed = json!({ "name": NAME, "done": true, "error": { "code": 5, "message": "Bucket not found.", "details": details } });
+
+        let error = operation::<Translated>(failed, &server)?.result().unwrap().unwrap_err();
+
+        let error = error.downcast_ref::<ServiceError>().expect("error is a ServiceError");
+        assert_eq!((error.http_status(), error.status(), error.message()), (404, Some("NOT_FOUND"), "Bucket not found."));
+        assert_eq!(error.details().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn result_of_an_operation_done_without_a_response() -> Result<()> {
+        let server = OperationServer::new(vec![], Duration::ZERO);
+        let done = json!({ "name": NAME, "done": true });
+
+        assert!(operation::<()>(done.clone(), &server)?.result().unwrap().is_ok());
+        assert_eq!(operation::<Option<Translated>>(done.clone(), &server)?.result().unwrap()?, None);
+        let error = operation::<Translated>(done, &server)?.result().unwrap().unwrap_err();
+        assert_eq!(error.to_string(), format!("Operation {} is done without a response or an error!", NAME));
+
+        let unexpected = json!({ "name": NAME, "done": true, "response": { "count": "three" } });
+        assert!(operation::<Translated>(unexpected, &server)?.result().unwrap().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::{Method, Url};
+use serde_json::Value;
+use tracing::Instrument;
+
+use super::{ApiMethod, ServiceBase};
+
+
+/// Page size and page token of a request to a list method.
+#[derive(Debug, Clone, Default)]
+pub struct PageRequest {
+    pub page_size: Option<u32>,
+    pub page_token: Option<String>,
+}
+
+/// One page of the results of a list method.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Token of the next page, or `None` on the last page.
+    /// Pass it to `Paginator::with_page_token` to resume listing after this page.
+    pub next_page_token: Option<String>,
+}
+
+type FetchPage<T> = Arc<dyn Fn(PageRequest) -> BoxFuture<'static, Result<Page<T>>> + Send + Sync>;
+
+struct PageState<T> {
+    fetch: FetchPage<T>,
+    page_size: Option<u32>,
+    page_token: Option<String>,
+    remaining: Option<usize>,
+    finished: bool,
+}
+
+
+/// Lists the results of a method using `pageToken` and `nextPageToken`, as a `Stream` of pages or of items.
+///
+/// Pages are fetched lazily as the stream is polled, so dropping the stream stops listing.
+/// To resume later, keep the `next_page_token` of the last page processed with `pages` and pass it to `with_page_token`.
+pub struct Paginator<T> {
+    fetch: FetchPage<T>,
+    page_size: Option<u32>,
+    page_token: Option<String>,
+    max_items: Option<usize>,
+}
+
+impl<T> fmt::Debug for Paginator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Paginator")
+            .field("page_size", &self.page_size)
+            .field("page_token", &self.page_token)
+            .field("max_items", &self.max_items)
+            .finish()
+    }
+}
+
+impl<T: Send + 'static> Paginator<T> {
+    /// Create `Paginator` for any list method.
+    ///
+    /// * `fetch` -  Function fetching the page for the given page size and page token.
+    pub fn new<F, Fut>(fetch: F) -> Self
+    where
+        F: Fn(PageRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Page<T>>> + Send + 'static,
+    {
+        Self {
+            fetch: Arc::new(move |request| Box::pin(fetch(request))),
+            page_size: None,
+            page_token: None,
+            max_items: None,
+        }
+    }
+
+    /// Maximum number of items per page. The server may return fewer. Defaults to the server's page size.
+    ///
+    /// * `page_size` -  Number of items per page.
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Start listing from a page token returned earlier, instead of from the first page.
+    ///
+    /// * `page_token` -  `next_page_token` of the last page processed.
+    pub fn with_page_token(mut self, page_token: String) -> Self {
+        self.page_token = Some(page_token);
+        self
+    }
+
+    /// Stop listing after this many items. The page size of each request is capped at the number of items left,
+    /// so the `next_page_token` of the last page resumes right after it. Should the server still return more items,
+    /// the page is truncated and has no `next_page_token`, since resuming from it would skip the items left out.
+    ///
+    /// * `max_items` -  Maximum number of items to return.
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Stream of the pages. The stream ends after the last page or the first error.
+    pub fn pages(self) -> impl Stream<Item = Result<Page<T>>> + Send {
+        let state = PageState {
+            fetch: self.fetch,
+            page_size: self.page_size,
+            page_token: self.page_token,
+            remaining: self.max_items,
+            finished: false,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            if state.finished || state.remaining == Some(0) {
+                return None;
+            }
+
+            let page_size = match state.remaining {
+                Some(remaining) => {
+                    let remaining = u32::try_from(remaining).unwrap_or(u32::MAX);
+                    Some(state.page_size.map_or(remaining, |page_size| page_size.min(remaining)))
+                }
+                None => state.page_size,
+            };
+            let request = PageRequest { page_size, page_token: state.page_token.clone() };
+            match (state.fetch)(request).await {
+                Ok(mut page) => {
+                    page.next_page_token = page.next_page_token.filter(|token| !token.is_empty());
+                    if let Some(remaining) = state.remaining.as_mut() {
+                        if page.items.len() > *remaining {
+                            page.items.truncate(*remaining);
+                            page.next_page_token = None;
+                        }
+                        *remaining -= page.items.len();
+                    }
+                    state.page_token = page.next_page_token.clone();
+                    state.finished = state.page_token.is_none();
+                    Some((Ok(page), state))
+                }
+                Err(error) => {
+                    state.finished = true;
+                    Some((Err(error), state))
+                }
+            }
+        })
+    }
+
+    /// Stream of the items of all pages. The stream ends after the last item or the first error.
+    pub fn items(self) -> impl Stream<Item = Result<T>> + Send {
+        self.pages()
+            .map_ok(|page| stream::iter(page.items.into_iter().map(Ok)))
+            .try_flatten()
+    }
+}
+
+
+impl ServiceBase {
+    /// Paginate a list method that takes the `pageSize` and `pageToken` query parameters
+    /// and returns its items in `items_field`, with the `nextPageToken` of the next page.
+    /// The pages share a copy of the service, so its access token is reused across pages.
+    ///
+    /// * `url` -  URL of the list method, with any other query parameters.
+    /// * `items_field` -  Field of the response holding the items, such as `operations`.
+    /// * `method` -  `ApiMethod` of the list method.
+    /// * `item` -  Function decoding an item.
+    // only the Cloud Translation API has list methods so far
+    #[cfg_attr(not(feature = "translate"), allow(dead_code))]
+    pub(super) fn paginate<T, F>(&self, url: Url, items_field: &'static str, method: &'static ApiMethod, item: F) -> Paginator<T>
+    where
+        T: Send + 'static,
+        F: Fn(Value) -> Result<T> + Send + Sync + 'static,
+    {
+        let base = Arc::new(tokio::sync::Mutex::new(self.clone()));
+        let item = Arc::new(item);
+        Paginator::new(move |page_request: PageRequest| {
+            let base = base.clone();
+            let item = item.clone();
+            let mut url = url.clone();
+            let span = tracing::info_span!("list_page", method = method.name, endpoint = tracing::field::Empty, status = tracing::field::Empty, latency_ms = tracing::field::Empty, retry_count = tracing::field::Empty, response_size = tracing::field::Empty);
+
+            async move {
+                if let Some(page_size) = page_request.page_size {
+                    url.query_pairs_mut().append_pair("pageSize", &page_size.to_string());
+                }
+                if let Some(page_token) = &page_request.page_token {
+                    url.query_pairs_mut().append_pair("pageToken", page_token);
+                }
+
+                let mut base = base.lock().await;
+                let headers = base.create_headers().await?;
+                let request = base.build_request(Method::GET, url, headers, vec![])?;
+                let body = base.make_request(request, method).await?;
+
+                let mut response: Value = serde_json::from_str(&body)?;
+                let items = match response.get_mut(items_field).map(Value::take) {
+                    Some(Value::Array(items)) => items,
+                    _ => vec![],
+                };
+                Ok(Page {
+                    items: items.into_iter().map(|value| item(value)).collect::<Result<_>>()?,
+                    next_page_token: response.get("nextPageToken").and_then(Value::as_str).map(str::to_owned),
+                })
+            }
+            .instrument(span)
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures::StreamExt;
+
+    use super::*;
+
+    /// Paginator over `0..total`, serving at most the requested page size, or `served_page_size` items per page
+    /// when `honor_page_size` is false.
+    fn numbers(total: u32, served_page_size: u32, honor_page_size: bool) -> (Paginator<u32>, Arc<Mutex<Vec<PageRequest>>>) {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let recorded = requests.clone();
+        let paginator = Paginator::new(move |request: PageRequest| {
+            recorded.lock().unwrap().push(request.clone());
+            async move {
+                let start: u32 = request.page_token.as_deref().map_or(Ok(0), str::parse)?;
+                let page_size = request.page_size.filter(|_| honor_page_size).map_or(served_page_size, |page_size| page_size.min(served_page_size));
+                let end = (start + page_size).min(total);
+                Ok(Page {
+                    items: (start..end).collect(),
+                    next_page_token: if end < total { Some(end.to_string()) } else { Some(String::new()) },
+                })
+            }
+        });
+        (paginator, requests)
+    }
+
+    #[tokio::test]
+    async fn lists_all_pages() {
+        let (paginator, requests) = numbers(5, 2, true);
+        let items: Vec<u32> = paginator.with_page_size(2).items().map(Result::unwrap).collect().await;
+
+        assert_eq!(items, [0, 1, 2, 3, 4]);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|request| request.page_size == Some(2)));
+    }
+
+    #[tokio::test]
+    async fn page_size_is_capped_at_the_items_left() {
+        let (paginator, requests) = numbers(10, 3, true);
+        let pages: Vec<Page<u32>> = paginator.with_page_size(3).with_max_items(5).pages().map(Result::unwrap).collect().await;
+
+        assert_eq!(pages.iter().map(|page| page.items.len()).collect::<Vec<_>>(), [3, 2]);
+        assert_eq!(pages[1].next_page_token.as_deref(), Some("5"));
+        let page_sizes: Vec<_> = requests.lock().unwrap().iter().map(|request| request.page_size).collect();
+        assert_eq!(page_sizes, [Some(3), Some(2)]);
+    }
+
+    #[tokio::test]
+    async fn over_long_page_is_truncated_without_token() {
+        // the server ignores the requested page size
+        let (paginator, requests) = numbers(10, 4, false);
+        let pages: Vec<Page<u32>> = paginator.with_max_items(6).pages().map(Result::unwrap).collect().await;
+
+        assert_eq!(pages.iter().map(|page| page.items.clone()).collect::<Vec<_>>(), [vec![0, 1, 2, 3], vec![4, 5]]);
+        assert_eq!(pages[0].next_page_token.as_deref(), Some("4"));
+        assert_eq!(pages[1].next_page_token, None);
+        let page_sizes: Vec<_> = requests.lock().unwrap().iter().map(|request| request.page_size).collect();
+        assert_eq!(page_sizes, [Some(6), Some(2)]);
+    }
+
+    #[tokio::test]
+    async fn resumes_from_page_token() {
+        let (paginator, requests) = numbers(5, 2, true);
+        let items: Vec<u32> = paginator.with_page_token("2".to_owned()).items().map(Result::unwrap).collect().await;
+
+        assert_eq!(items, [2, 3, 4]);
+        assert_eq!(requests.lock().unwrap()[0].page_token.as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn stops_after_the_first_error() {
+        let paginator: Paginator<u32> = Paginator::new(|_| async { Err(anyhow::anyhow!("failed")) });
+        let pages: Vec<_> = paginator.pages().collect().await;
+
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].is_err());
+    }
+}
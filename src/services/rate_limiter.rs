@@ -0,0 +1,214 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::{self, Instant};
+
+
+/// Token bucket that lets requests go into debt, so waiting requests are served in the order they arrived.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    tokens_per_second: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, period: Duration) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            tokens: capacity,
+            tokens_per_second: capacity / period.as_secs_f64(),
+            updated_at: Instant::now(),
+        }
+    }
+
+    /// Take the tokens and return how many were taken and how long to wait until they are available.
+    /// Costs larger than the capacity take the whole bucket, so they wait instead of blocking forever.
+    fn reserve(&mut self, cost: f64) -> (f64, Duration) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.tokens_per_second).min(self.capacity);
+        self.updated_at = now;
+
+        let taken = cost.min(self.capacity);
+        self.tokens -= taken;
+        if self.tokens >= 0.0 {
+            (taken, Duration::ZERO)
+        } else {
+            (taken, Duration::from_secs_f64(-self.tokens / self.tokens_per_second))
+        }
+    }
+
+    /// Give back tokens taken by a request that stopped waiting before its turn.
+    fn refund(&mut self, tokens: f64) {
+        self.tokens = (self.tokens + tokens).min(self.capacity);
+    }
+}
+
+
+/// Tokens taken from a bucket by a waiting request. They are given back if the reservation is dropped
+/// before `complete`, such as when the request is cancelled or times out while waiting.
+struct Reservation<'a> {
+    bucket: Option<&'a Mutex<TokenBucket>>,
+    tokens: f64,
+    delay: Duration,
+}
+
+impl Reservation<'_> {
+    fn complete(mut self) {
+        self.bucket = None;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if let Some(bucket) = self.bucket {
+            bucket.lock().unwrap_or_else(|e| e.into_inner()).refund(self.tokens);
+        }
+    }
+}
+
+
+/// Client-side rate limiter for the requests of a service.
+///
+/// Limits queries per second, queries per minute and, for Cloud Translation, characters per minute.
+/// Requests over the limits wait for their turn instead of being sent and rejected with `429 RESOURCE_EXHAUSTED`.
+/// Cloning the limiter is cheap, and clones share the same budget, so the same limiter can be given to several
+/// services, or clones of a service, that use the same project quota.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    queries_per_second: Option<Arc<Mutex<TokenBucket>>>,
+    queries_per_minute: Option<Arc<Mutex<TokenBucket>>>,
+    characters_per_minute: Option<Arc<Mutex<TokenBucket>>>,
+}
+
+impl RateLimiter {
+    /// Create `RateLimiter` without limits. Add them with the `with_*` methods.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of requests per second. Up to `queries_per_second` requests can be sent at once.
+    ///
+    /// * `queries_per_second` -  Maximum number of requests per second.
+    pub fn with_queries_per_second(mut self, queries_per_second: u32) -> Self {
+        self.queries_per_second = Some(Arc::new(Mutex::new(TokenBucket::new(queries_per_second, Duration::from_secs(1)))));
+        self
+    }
+
+    /// Limit the number of requests per minute.
+    ///
+    /// * `queries_per_minute` -  Maximum number of requests per minute.
+    pub fn with_queries_per_minute(mut self, queries_per_minute: u32) -> Self {
+        self.queries_per_minute = Some(Arc::new(Mutex::new(TokenBucket::new(queries_per_minute, Duration::from_secs(60)))));
+        self
+    }
+
+    /// Limit the number of characters sent to Cloud Translation per minute. Other services ignore this limit.
+    ///
+    /// * `characters_per_minute` -  Maximum number of characters per minute.
+    pub fn with_characters_per_minute(mut self, characters_per_minute: u32) -> Self {
+        self.characters_per_minute = Some(Arc::new(Mutex::new(TokenBucket::new(characters_per_minute, Duration::from_secs(60)))));
+        self
+    }
+
+    /// Wait until a request can be sent. The budget is given back if the future is dropped while waiting.
+    pub async fn acquire_query(&self) {
+        let per_second = Self::reserve(&self.queries_per_second, 1.0);
+        let per_minute = Self::reserve(&self.queries_per_minute, 1.0);
+        let delay = per_second.delay.max(per_minute.delay);
+        if !delay.is_zero() {
+            tracing::debug!(delay_ms = delay.as_millis() as u64, "waiting for rate limiter");
+            time::sleep(delay).await;
+        }
+        per_second.complete();
+        per_minute.complete();
+    }
+
+    /// Wait until the characters can be sent. The budget is given back if the future is dropped while waiting.
+    ///
+    /// * `characters` -  Number of characters in the request.
+    pub async fn acquire_characters(&self, characters: usize) {
+        let reservation = Self::reserve(&self.characters_per_minute, characters as f64);
+        if !reservation.delay.is_zero() {
+            tracing::debug!(characters, delay_ms = reservation.delay.as_millis() as u64, "waiting for character budget");
+            time::sleep(reservation.delay).await;
+        }
+        reservation.complete();
+    }
+
+    fn reserve(bucket: &Option<Arc<Mutex<TokenBucket>>>, cost: f64) -> Reservation<'_> {
+        match bucket {
+            Some(bucket) => {
+                let (tokens, delay) = bucket.lock().unwrap_or_else(|e| e.into_inner()).reserve(cost);
+                Reservation { bucket: Some(bucket), tokens, delay }
+            }
+            None => Reservation { bucket: None, tokens: 0.0, delay: Duration::ZERO },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn requests_go_into_debt() {
+        let mut bucket = TokenBucket::new(2, Duration::from_secs(1));
+
+        assert_eq!(bucket.reserve(1.0), (1.0, Duration::ZERO));
+        assert_eq!(bucket.reserve(1.0), (1.0, Duration::ZERO));
+        assert_eq!(bucket.reserve(1.0), (1.0, Duration::from_millis(500)));
+        assert_eq!(bucket.reserve(1.0), (1.0, Duration::from_secs(1)));
+
+        time::advance(Duration::from_secs(1)).await;
+        assert_eq!(bucket.reserve(1.0), (1.0, Duration::from_millis(500)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cost_is_capped_at_the_capacity() {
+        let mut bucket = TokenBucket::new(10, Duration::from_secs(60));
+
+        assert_eq!(bucket.reserve(25.0), (10.0, Duration::ZERO));
+        assert_eq!(bucket.reserve(25.0), (10.0, Duration::from_secs(60)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dropped_reservation_is_refunded() {
+        let bucket = Some(Arc::new(Mutex::new(TokenBucket::new(1, Duration::from_secs(1)))));
+
+        RateLimiter::reserve(&bucket, 1.0).complete();
+        let waiting = RateLimiter::reserve(&bucket, 1.0);
+        assert_eq!(waiting.delay, Duration::from_secs(1));
+        drop(waiting);
+
+        assert_eq!(RateLimiter::reserve(&bucket, 1.0).delay, Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_waits_for_the_strictest_limit() {
+        let limiter = RateLimiter::new().with_queries_per_second(10).with_queries_per_minute(1);
+
+        let started = Instant::now();
+        limiter.acquire_query().await;
+        limiter.acquire_query().await;
+        assert_eq!(started.elapsed(), Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelled_acquire_gives_the_budget_back() {
+        let limiter = RateLimiter::new().with_characters_per_minute(100);
+        limiter.acquire_characters(100).await;
+
+        let cancelled = time::timeout(Duration::from_secs(1), limiter.acquire_characters(100)).await;
+        assert!(cancelled.is_err());
+
+        // only the first 100 characters are owed, and 1 second of them was refilled
+        let started = Instant::now();
+        limiter.acquire_characters(100).await;
+        assert_eq!(started.elapsed(), Duration::from_secs(59));
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::pin;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::future::{self, Either};
+#[cfg(feature = "grpc")]
+use futures::{stream, Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+static REQUEST_ID_HEADER: &str = "x-request-id";
+static REQUEST_PARAMS_HEADER: &str = "x-goog-request-params";
+
+
+/// Error returned when a request is interrupted by its `RequestOptions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    /// The request, including its retries, did not finish within the timeout.
+    DeadlineExceeded(Duration),
+    /// The cancellation token was cancelled before the request finished.
+    Cancelled,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::DeadlineExceeded(timeout) => write!(f, "Request Error! Deadline of {:?} exceeded", timeout),
+            RequestError::Cancelled => write!(f, "Request Error! Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+
+/// Options of a single call, passed to the `*_with_options` variants of the service methods.
+///
+/// The timeout covers the whole call, including fetching an access token, waiting for the rate limiter and retries.
+/// Interrupted calls fail with a `RequestError`.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+    request_id: Option<String>,
+    request_params: Option<String>,
+    cancellation_token: Option<CancellationToken>,
+}
+
+impl RequestOptions {
+    /// Create `RequestOptions` without timeout, extra headers or cancellation token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail the call with `RequestError::DeadlineExceeded` if it does not finish in time.
+    ///
+    /// * `timeout` -  Maximum duration of the call.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send an extra header with the request. Invalid names or values fail the call.
+    ///
+    /// * `name` -  Name of the header.
+    /// * `value` -  Value of the header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Send an id with the request in the `x-request-id` header, to correlate it with the logs of proxies and servers.
+    ///
+    /// * `request_id` -  Id of the request.
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_owned());
+        self
+    }
+
+    /// Send the `x-goog-request-params` routing header, such as `parent=projects/my-project/locations/us-central1`.
+    ///
+    /// * `request_params` -  URL-encoded routing parameters.
+    pub fn with_request_params(mut self, request_params: &str) -> Self {
+        self.request_params = Some(request_params.to_owned());
+        self
+    }
+
+    /// Fail the call with `RequestError::Cancelled` as soon as the token is cancelled.
+    ///
+    /// * `cancellation_token` -  `CancellationToken` of the call. Child tokens cancel the call when their parent is cancelled.
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = Some(cancellation_token);
+        self
+    }
+
+    /// Add the extra headers, request id and request params to the headers of a request.
+    pub(super) fn apply_headers(&self, headers: &mut HeaderMap) -> Result<()> {
+        for (name, value) in &self.headers {
+            headers.insert(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?);
+        }
+        if let Some(request_id) = &self.request_id {
+            headers.insert(REQUEST_ID_HEADER, HeaderValue::from_str(request_id)?);
+        }
+        if let Some(request_params) = &self.request_params {
+            headers.insert(REQUEST_PARAMS_HEADER, HeaderValue::from_str(request_params)?);
+        }
+        Ok(())
+    }
+
+    /// Run a call, interrupting it on timeout or cancellation.
+    pub(super) async fn run<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        let call = async {
+            match self.timeout {
+                Some(timeout) => time::timeout(timeout, call).await.map_err(|_| RequestError::DeadlineExceeded(timeout))?,
+                None => call.await,
+            }
+        };
+
+        match &self.cancellation_token {
+            Some(cancellation_token) if cancellation_token.is_cancelled() => Err(RequestError::Cancelled.into()),
+            Some(cancellation_token) => match future::select(pin!(call), pin!(cancellation_token.cancelled())).await {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => Err(RequestError::Cancelled.into()),
+            },
+            None => call.await,
+        }
+    }
+    /// Interrupt the stream of a streaming call on timeout or cancellation, counting the timeout from `started`.
+    /// The stream yields the `RequestError` and ends.
+    #[cfg(feature = "grpc")]
+    pub(super) fn run_stream<T>(&self, started: time::Instant, elements: impl Stream<Item = Result<T>>) -> impl Stream<Item = Result<T>> {
+        let deadline = self.timeout.map(|timeout| (started + timeout, timeout));
+        let cancellation_token = self.cancellation_token.clone();
+
+        stream::unfold(Some(Box::pin(elements)), move |elements| {
+            let cancellation_token = cancellation_token.clone();
+            async move {
+                let mut elements = elements?;
+                if cancellation_token.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                    return Some((Err(RequestError::Cancelled.into()), None));
+                }
+                let next = async {
+                    match deadline {
+                        Some((deadline, timeout)) => time::timeout_at(deadline, elements.next()).await.map_err(|_| RequestError::DeadlineExceeded(timeout)),
+                        None => Ok(elements.next().await),
+                    }
+                };
+                let next = match &cancellation_token {
+                    Some(cancellation_token) => match future::select(pin!(next), pin!(cancellation_token.cancelled())).await {
+                        Either::Left((next, _)) => next,
+                        Either::Right(_) => Err(RequestError::Cancelled),
+                    },
+                    None => next.await,
+                };
+
+                match next {
+                    Ok(Some(element)) => Some((element, Some(elements))),
+                    Ok(None) => None,
+                    Err(error) => Some((Err(error.into()), None)),
+                }
+            }
+        })
+    }
+}
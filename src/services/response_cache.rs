@@ -0,0 +1,250 @@
+use std::fmt::Debug;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ApiMethod, ServiceBase};
+use super::http_service::{self, HttpRequest};
+
+// request fields holding a time, bucketed so that requests a few minutes apart share a response
+static BUCKETED_TIME_FIELDS: [&str; 2] = ["departureTime", "arrivalTime"];
+
+
+/// Storage of a `ResponseCache`. Implement it to keep responses in a shared store such as Redis.
+#[async_trait]
+pub trait CacheBackend: Debug + Send + Sync {
+    /// Response stored for the key, if any and not expired.
+    async fn get(&self, key: &str) -> Option<String>;
+
+    /// Store a response for the key.
+    ///
+    /// * `key` -  Key of the request.
+    /// * `body` -  Body of the response.
+    /// * `ttl` -  How long the response can be returned for.
+    async fn put(&self, key: &str, body: &str, ttl: Duration);
+}
+
+
+/// `CacheBackend` keeping the most recently used responses in memory.
+#[derive(Debug)]
+pub struct InMemoryCache {
+    entries: Mutex<LruCache<String, (String, Instant)>>,
+}
+
+impl InMemoryCache {
+    /// Create `InMemoryCache`.
+    ///
+    /// * `capacity` -  Maximum number of responses. The least recently used ones are evicted first.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self { entries: Mutex::new(LruCache::new(capacity)) }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for InMemoryCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some((body, expires_at)) if *expires_at > Instant::now() => Some(body.clone()),
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn put(&self, key: &str, body: &str, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.put(key.to_owned(), (body.to_owned(), Instant::now() + ttl));
+    }
+}
+
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    expires_at: u64,
+    body: String,
+}
+
+/// `CacheBackend` keeping responses in files of a directory, so they survive restarts.
+/// Expired files are removed when they are read.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Create `DiskCache`, creating the directory if needed.
+    ///
+    /// * `dir` -  Directory of the cache files.
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(Self { dir: dir.as_ref().to_owned() })
+    }
+
+    // FNV-1a, stable across builds unlike the std hashers; the key stored in the file guards against collisions
+    fn path(&self, key: &str) -> PathBuf {
+        let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3));
+        self.dir.join(format!("{:016x}.json", hash))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs()).unwrap_or_default()
+}
+
+#[async_trait]
+impl CacheBackend for DiskCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let path = self.path(key);
+        let entry: DiskEntry = serde_json::from_slice(&tokio::fs::read(&path).await.ok()?).ok()?;
+        if entry.expires_at <= unix_now() {
+            let _ = tokio::fs::remove_file(&path).await;
+            return None;
+        }
+        (entry.key == key).then_some(entry.body)
+    }
+
+    async fn put(&self, key: &str, body: &str, ttl: Duration) {
+        let path = self.path(key);
+        let entry = DiskEntry { key: key.to_owned(), expires_at: unix_now() + ttl.as_secs(), body: body.to_owned() };
+        let Ok(contents) = serde_json::to_vec(&entry) else { return };
+
+        // write to a temporary file first, so readers never see a partial entry
+        let temporary_path = path.with_extension(format!("{}.tmp", rand::random::<u32>()));
+        let written = match tokio::fs::write(&temporary_path, contents).await {
+            Ok(()) => tokio::fs::rename(&temporary_path, &path).await,
+            Err(error) => Err(error),
+        };
+        if let Err(error) = written {
+            tracing::debug!(error = %error, path = %path.display(), "failed to write cache entry");
+            let _ = tokio::fs::remove_file(&temporary_path).await;
+        }
+    }
+}
+
+
+/// Opt-in cache of the responses of idempotent calls: `translate`, `detect_language`, `list_languages`,
+/// `get_route` and `get_route_matrix`.
+///
+/// Responses are keyed by the caller, the method, the URL, the field mask and the request body normalized as JSON,
+/// so the order of the fields does not matter. The caller is a hash of the API key, or the service account and
+/// subject, so services sharing a cache never see each other's responses. The `departureTime` and `arrivalTime` of Routes requests
+/// are rounded down to the departure time bucket, so ETAs requested a few minutes apart share a response.
+/// Cache hits skip the rate limiter and authentication, and calls sent over gRPC are not cached.
+/// Cloning the cache is cheap, and clones share the same backend.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    backend: Arc<dyn CacheBackend>,
+    ttl: Duration,
+    departure_time_bucket: Duration,
+}
+
+impl ResponseCache {
+    /// Create `ResponseCache` with a custom backend, a TTL of 1 hour and departure time buckets of 5 minutes.
+    ///
+    /// * `backend` -  `CacheBackend` storing the responses.
+    pub fn new<B: CacheBackend + 'static>(backend: B) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            ttl: Duration::from_secs(60 * 60),
+            departure_time_bucket: Duration::from_secs(5 * 60),
+        }
+    }
+
+    /// Create `ResponseCache` keeping responses in memory.
+    ///
+    /// * `capacity` -  Maximum number of responses.
+    pub fn in_memory(capacity: usize) -> Self {
+        Self::new(InMemoryCache::new(capacity))
+    }
+
+    /// Create `ResponseCache` keeping responses on disk.
+    ///
+    /// * `dir` -  Directory of the cache files.
+    pub fn on_disk(dir: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(DiskCache::new(dir)?))
+    }
+
+    /// How long a response can be returned for.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Round departure and arrival times down to a multiple of this duration in cache keys.
+    /// Use a shorter bucket for fresher traffic-aware ETAs, or zero to disable bucketing.
+    pub fn with_departure_time_bucket(mut self, bucket: Duration) -> Self {
+        self.departure_time_bucket = bucket;
+        self
+    }
+
+    fn key(&self, caller: &str, method: &ApiMethod, request: &HttpRequest) -> String {
+        let field_mask = request.headers().get("X-Goog-FieldMask").and_then(|mask| mask.to_str().ok()).unwrap_or_default();
+        let body = match serde_json::from_slice::<Value>(request.body()) {
+            Ok(mut body) => {
+                self.bucket_times(&mut body);
+                body.to_string()
+            }
+            Err(_) => String::from_utf8_lossy(request.body()).into_owned(),
+        };
+        format!("{} {}.{} {} {} {}", caller, method.service, method.name, http_service::redacted_uri(request.uri()), field_mask, body)
+    }
+
+    fn bucket_times(&self, body: &mut Value) {
+        let bucket = self.departure_time_bucket.as_secs() as i64;
+        if bucket == 0 {
+            return;
+        }
+        for field in BUCKETED_TIME_FIELDS {
+            let Some(time) = body.get_mut(field) else { continue };
+            if let Some(parsed) = time.as_str().and_then(|time| DateTime::parse_from_rfc3339(time).ok()) {
+                let timestamp = parsed.timestamp() - parsed.timestamp().rem_euclid(bucket);
+                if let Some(bucketed) = DateTime::<Utc>::from_timestamp(timestamp, 0) {
+                    *time = Value::String(bucketed.to_rfc3339_opts(SecondsFormat::Secs, true));
+                }
+            }
+        }
+    }
+}
+
+
+impl ServiceBase {
+    /// Return the cached response of the request if there is one, otherwise authenticate the request,
+    /// wait for the character budget, send the request and cache its response.
+    /// Cache hits need no access token, so the request is built without the authentication headers.
+    ///
+    /// * `request` -  Request to send, without the authentication headers.
+    /// * `method` -  `ApiMethod` of the request.
+    /// * `characters` -  Number of characters to translate, zero for other services.
+    pub(super) async fn make_cached_request(&mut self, mut request: HttpRequest, method: &ApiMethod, characters: usize) -> Result<String> {
+        let Some(cache) = self.response_cache.clone() else {
+            self.add_auth_headers(request.headers_mut()).await?;
+            self.acquire_characters(characters).await;
+            return self.make_request(request, method).await;
+        };
+
+        let key = cache.key(&self.caller_identity(), method, &request);
+        if let Some(body) = cache.backend.get(&key).await {
+            tracing::debug!(method = method.name, "response cache hit");
+            return Ok(body);
+        }
+
+        self.add_auth_headers(request.headers_mut()).await?;
+        self.acquire_characters(characters).await;
+        let body = self.make_request(request, method).await?;
+        cache.backend.put(&key, &body, cache.ttl).await;
+        Ok(body)
+    }
+}
@@ -0,0 +1,282 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+use super::service_error::ServiceError;
+
+static RETRYABLE_STATUS_CODES: [StatusCode; 3] = [
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+
+
+/// Policy for retrying transient failures: 429, 503 and 504 responses, and connection errors.
+///
+/// The delay before a retry is taken from the `Retry-After` header or the `RetryInfo` error detail if the server sends one,
+/// otherwise it grows exponentially from `initial_backoff` up to `max_backoff`, with random jitter.
+/// If the server asks for a delay longer than `max_backoff`, the error is returned instead of waiting.
+/// Only idempotent requests are retried unless `with_retry_non_idempotent` is set.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(32),
+            multiplier: 2.0,
+            jitter: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create `RetryPolicy` with 3 attempts, backoff starting at 1 second and doubling up to 32 seconds, with jitter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Policy that sends every request once. This is the default for services.
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Maximum number of attempts, including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Delay before the first retry.
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Upper bound of the delay between retries. Errors asking for a longer delay are not retried.
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Factor the delay grows by after each retry.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Randomize each delay between half and the full backoff, so that clients do not retry in lockstep.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Also retry requests that are not idempotent.
+    pub fn with_retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    pub(crate) fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub(crate) fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    pub(crate) fn retries_non_idempotent(&self) -> bool {
+        self.retry_non_idempotent
+    }
+
+    /// Delay before retrying after the given attempt, starting at 1.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        if self.jitter {
+            Duration::from_secs_f64(rand::thread_rng().gen_range(backoff / 2.0..=backoff))
+        } else {
+            Duration::from_secs_f64(backoff)
+        }
+    }
+}
+
+
+pub(crate) fn is_retryable_status(status_code: StatusCode) -> bool {
+    RETRYABLE_STATUS_CODES.contains(&status_code)
+}
+
+/// Connection failures and connections reset before a response was received.
+/// Timeouts are not retried, since the server may still be processing the request.
+pub(crate) fn is_retryable_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if error.downcast_ref::<reqwest::Error>().is_some_and(reqwest::Error::is_connect) {
+            return true;
+        }
+        let is_reset = error.downcast_ref::<std::io::Error>().is_some_and(|io_error| matches!(
+            io_error.kind(),
+            std::io::ErrorKind::ConnectionReset | std::io::ErrorKind::ConnectionAborted | std::io::ErrorKind::BrokenPipe
+        ));
+        if is_reset {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// Delay requested by the `Retry-After` header, either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+/// Delay requested by a `google.rpc.RetryInfo` error detail, such as `{"retryDelay": "1.5s"}`.
+pub(crate) fn retry_info_delay(error: &ServiceError) -> Option<Duration> {
+    error.retry_info()?.delay()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    fn headers(retry_after: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(retry_after).unwrap());
+        headers
+    }
+
+    #[test]
+    fn backoff_grows_up_to_max_backoff() {
+        let policy = RetryPolicy::new()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500))
+            .with_multiplier(2.0)
+            .with_jitter(false);
+        let backoffs: Vec<u128> = (1..=5).map(|attempt| policy.backoff(attempt).as_millis()).collect();
+        assert_eq!(backoffs, [100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn jitter_stays_within_half_and_full_backoff() {
+        let policy = RetryPolicy::new().with_initial_backoff(Duration::from_millis(100)).with_multiplier(3.0);
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff >= Duration::from_millis(150) && backoff <= Duration::from_millis(300), "{:?}", backoff);
+        }
+    }
+
+    #[test]
+    fn multiplier_and_max_attempts_are_bounded() {
+        let policy = RetryPolicy::new().with_max_attempts(0).with_multiplier(0.5).with_jitter(false);
+        assert_eq!(policy.max_attempts(), 1);
+        assert_eq!(policy.backoff(3), policy.backoff(1));
+        assert_eq!(RetryPolicy::none().max_attempts(), 1);
+    }
+
+    #[test]
+    fn retryable_status_codes() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::GATEWAY_TIMEOUT));
+        assert!(!is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn retry_after_in_seconds() {
+        assert_eq!(retry_after(&headers("120")), Some(Duration::from_secs(120)));
+        assert_eq!(retry_after(&headers(" 0 ")), Some(Duration::ZERO));
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+        assert_eq!(retry_after(&headers("soon")), None);
+    }
+
+    #[test]
+    fn retry_after_as_http_date() {
+        let date = (Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let delay = retry_after(&headers(&date)).unwrap();
+        assert!(delay > Duration::from_secs(28) && delay <= Duration::from_secs(30), "{:?}", delay);
+
+        // dates in the past ask for no delay
+        assert_eq!(retry_after(&headers("Wed, 21 Oct 2015 07:28:00 GMT")), None);
+    }
+
+    #[test]
+    fn retry_info_delay_of_error_details() {
+        let retry_info = json!({ "@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "1.5s" });
+        let error = ServiceError::from_rpc_status(8, "Quota exceeded", vec![retry_info]);
+        assert_eq!(retry_info_delay(&error), Some(Duration::from_millis(1500)));
+
+        let error = ServiceError::from_rpc_status(8, "Quota exceeded", vec![]);
+        assert_eq!(retry_info_delay(&error), None);
+    }
+
+    #[derive(Debug)]
+    struct Wrapped(&'static str, Option<Box<dyn std::error::Error + Send + Sync>>);
+
+    impl std::fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl std::error::Error for Wrapped {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.1.as_deref().map(|error| error as &(dyn std::error::Error + 'static))
+        }
+    }
+
+    #[test]
+    fn reset_below_an_unrelated_io_error_is_retryable() {
+        let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        let tls = Wrapped("tls error", Some(Box::new(reset)));
+        let error = Wrapped("request failed", Some(Box::new(std::io::Error::other(tls))));
+        assert!(is_retryable_error(&error));
+
+        let unrelated = Wrapped("request failed", Some(Box::new(std::io::Error::from(std::io::ErrorKind::InvalidData))));
+        assert!(!is_retryable_error(&unrelated));
+    }
+
+    #[tokio::test]
+    async fn connect_errors_are_retryable_but_timeouts_are_not() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        // accept the connection and never answer
+        tokio::spawn(async move {
+            let _connection = listener.accept().await;
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let client = reqwest::Client::builder().timeout(Duration::from_millis(50)).build().unwrap();
+        let timeout = client.get(format!("http://{}/", addr)).send().await.unwrap_err();
+        assert!(timeout.is_timeout());
+        assert!(!is_retryable_error(&timeout));
+
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let connect = client.get(format!("http://{}/", closed)).send().await.unwrap_err();
+        assert!(connect.is_connect());
+        assert!(is_retryable_error(&connect));
+    }
+}
@@ -170,11 +170,7 @@ impl RouteModifiers {
     /// A set of optional conditions to satisfy when calculating the routes.
     /// See https://developers.google.com/maps/documentation/routes/reference/rest/v2/RouteModifiers
     pub fn new(avoid_tolls: Option<bool>, avoid_highways: Option<bool>, avoid_ferries: Option<bool>, avoid_indoor: Option<bool>, emission_type: Option<EmissionType>, toll_passes: Option<Vec<String>>) -> Self{
-        let vehicle_info:Option<VehicleInfo>= if let Some(emission_type) = emission_type {
-            Some(VehicleInfo {emission_type})
-        } else {
-            None
-        };
+        let vehicle_info:Option<VehicleInfo>= emission_type.map(|emission_type| VehicleInfo {emission_type});
         Self {
             avoid_tolls, avoid_highways, avoid_ferries, avoid_indoor,vehicle_info,toll_passes
         }
@@ -0,0 +1,666 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+
+// fields billed as Compute Routes Pro: optimized waypoint order, and route modifiers the route could not follow
+// See https://developers.google.com/maps/documentation/routes/usage-and-billing
+static PRO_PATHS: [&str; 3] = [
+    "routes.optimizedIntermediateWaypointIndex",
+    "routes.travelAdvisory.routeRestrictionsPartiallyIgnored",
+    "travelAdvisory.routeRestrictionsPartiallyIgnored",
+];
+
+// fields billed as Compute Routes Enterprise (formerly Preferred): toll calculation, eco-friendly routing and traffic on polylines
+// See https://developers.google.com/maps/documentation/routes/usage-and-billing
+static ENTERPRISE_PATHS: [&str; 9] = [
+    "routes.travelAdvisory.tollInfo",
+    "routes.travelAdvisory.fuelConsumptionMicroliters",
+    "routes.travelAdvisory.speedReadingIntervals",
+    "routes.legs.travelAdvisory.tollInfo",
+    "routes.legs.travelAdvisory.speedReadingIntervals",
+    "routes.legs.steps.travelAdvisory.speedReadingIntervals",
+    "travelAdvisory.tollInfo",
+    "travelAdvisory.fuelConsumptionMicroliters",
+    "travelAdvisory.speedReadingIntervals",
+];
+
+
+/// Billing SKU of a Routes API request.
+/// See https://developers.google.com/maps/documentation/routes/usage-and-billing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RoutesSku {
+    Essentials,
+    Pro,
+    Enterprise,
+}
+
+impl RoutesSku {
+    /// SKU triggered by a response field mask. `*`, or no mask at all, is billed as `Enterprise`.
+    /// The optimized waypoint order is billed as `Pro`, tolls, fuel consumption and traffic on polylines as `Enterprise`.
+    /// Request options can raise the SKU further: `TRAFFIC_AWARE` routing and more than 10 intermediate waypoints
+    /// are billed as `Pro`, `TRAFFIC_AWARE_OPTIMAL` routing and eco-friendly routes as `Enterprise`.
+    ///
+    /// * `response_masks` -  Response field mask, as passed to `get_route` or `get_route_matrix`.
+    pub fn of_masks(response_masks: Option<&[&str]>) -> Self {
+        match response_masks {
+            Some(masks) => masks.iter().map(|mask| Self::of_path(mask)).max().unwrap_or(RoutesSku::Essentials),
+            None => RoutesSku::Enterprise,
+        }
+    }
+
+    fn of_path(path: &str) -> Self {
+        let path = path.trim();
+        if path == "*" || selects_any(path, &ENTERPRISE_PATHS) {
+            RoutesSku::Enterprise
+        } else if selects_any(path, &PRO_PATHS) {
+            RoutesSku::Pro
+        } else {
+            RoutesSku::Essentials
+        }
+    }
+}
+
+// a path selects a billed field if it is the field, one of its parents or one of its children
+fn selects_any(path: &str, billed_paths: &[&str]) -> bool {
+    billed_paths.iter().any(|billed| {
+        billed == &path
+            || billed.strip_prefix(path).is_some_and(|rest| rest.starts_with('.'))
+            || path.strip_prefix(billed).is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+
+/// A field of a response that can be selected by a field mask.
+pub trait MaskField {
+    /// Path of the field in the response, such as `routes.legs.duration`.
+    fn path(&self) -> String;
+}
+
+/// Type-checked response field mask for the Routes API.
+///
+/// Fields are chosen from enums that follow the response models, so a misspelled or misplaced path does not compile.
+/// Use `RoutesFieldMask` for `get_route` and `RouteMatrixFieldMask` for `get_route_matrix`, and pass `paths()` as `response_masks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMask<F> {
+    paths: Vec<String>,
+    _field: PhantomData<F>,
+}
+
+impl<F: MaskField> Default for FieldMask<F> {
+    fn default() -> Self {
+        Self { paths: vec![], _field: PhantomData }
+    }
+}
+
+impl<F: MaskField> FieldMask<F> {
+    /// Create an empty `FieldMask`. Add fields with `with`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field to the mask. Fields already selected are not added twice.
+    ///
+    /// * `field` -  Field to return.
+    pub fn with(mut self, field: F) -> Self {
+        let path = field.path();
+        if !self.paths.contains(&path) {
+            self.paths.push(path);
+        }
+        self
+    }
+
+    /// Paths of the mask, to pass as `response_masks`.
+    pub fn paths(&self) -> Vec<&str> {
+        self.paths.iter().map(String::as_str).collect()
+    }
+
+    /// Billing SKU the mask triggers.
+    pub fn sku(&self) -> RoutesSku {
+        RoutesSku::of_masks(Some(&self.paths()))
+    }
+}
+
+impl<F> fmt::Display for FieldMask<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.paths.join(","))
+    }
+}
+
+/// Field mask for `get_route`.
+pub type RoutesFieldMask = FieldMask<ComputeRoutesField>;
+
+/// Field mask for `get_route_matrix`.
+pub type RouteMatrixFieldMask = FieldMask<RouteMatrixElementField>;
+
+
+fn join(parent: &str, child: Option<String>) -> String {
+    match child {
+        Some(child) => format!("{}.{}", parent, child),
+        None => parent.to_owned(),
+    }
+}
+
+/// Fields of `ComputeRouteResponse`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeRoutesField {
+    Routes(RouteField),
+    FallbackInfo,
+    GeocodingResults,
+}
+
+impl MaskField for ComputeRoutesField {
+    fn path(&self) -> String {
+        match self {
+            ComputeRoutesField::Routes(field) => join("routes", field.child_path()),
+            ComputeRoutesField::FallbackInfo => "fallbackInfo".to_owned(),
+            ComputeRoutesField::GeocodingResults => "geocodingResults".to_owned(),
+        }
+    }
+}
+
+/// Fields of `Route`. `All` selects the whole route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteField {
+    All,
+    RouteLabels,
+    Legs(RouteLegField),
+    DistanceMeters,
+    Duration,
+    StaticDuration,
+    Polyline(PolylineField),
+    Description,
+    Warnings,
+    Viewport,
+    TravelAdvisory(RouteTravelAdvisoryField),
+    OptimizedIntermediateWaypointIndex,
+    LocalizedValues,
+    RouteToken,
+}
+
+impl RouteField {
+    fn child_path(&self) -> Option<String> {
+        Some(match self {
+            RouteField::All => return None,
+            RouteField::RouteLabels => "routeLabels".to_owned(),
+            RouteField::Legs(field) => join("legs", field.child_path()),
+            RouteField::DistanceMeters => "distanceMeters".to_owned(),
+            RouteField::Duration => "duration".to_owned(),
+            RouteField::StaticDuration => "staticDuration".to_owned(),
+            RouteField::Polyline(field) => join("polyline", field.child_path()),
+            RouteField::Description => "description".to_owned(),
+            RouteField::Warnings => "warnings".to_owned(),
+            RouteField::Viewport => "viewport".to_owned(),
+            RouteField::TravelAdvisory(field) => join("travelAdvisory", field.child_path()),
+            RouteField::OptimizedIntermediateWaypointIndex => "optimizedIntermediateWaypointIndex".to_owned(),
+            RouteField::LocalizedValues => "localizedValues".to_owned(),
+            RouteField::RouteToken => "routeToken".to_owned(),
+        })
+    }
+}
+
+/// Fields of `RouteLeg`. `All` selects the whole leg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteLegField {
+    All,
+    DistanceMeters,
+    Duration,
+    StaticDuration,
+    Polyline(PolylineField),
+    StartLocation,
+    EndLocation,
+    Steps(RouteLegStepField),
+    TravelAdvisory(RouteLegTravelAdvisoryField),
+    LocalizedValues,
+    StepsOverview,
+}
+
+impl RouteLegField {
+    fn child_path(&self) -> Option<String> {
+        Some(match self {
+            RouteLegField::All => return None,
+            RouteLegField::DistanceMeters => "distanceMeters".to_owned(),
+            RouteLegField::Duration => "duration".to_owned(),
+            RouteLegField::StaticDuration => "staticDuration".to_owned(),
+            RouteLegField::Polyline(field) => join("polyline", field.child_path()),
+            RouteLegField::StartLocation => "startLocation".to_owned(),
+            RouteLegField::EndLocation => "endLocation".to_owned(),
+            RouteLegField::Steps(field) => join("steps", field.child_path()),
+            RouteLegField::TravelAdvisory(field) => join("travelAdvisory", field.child_path()),
+            RouteLegField::LocalizedValues => "localizedValues".to_owned(),
+            RouteLegField::StepsOverview => "stepsOverview".to_owned(),
+        })
+    }
+}
+
+/// Fields of `RouteLegStep`. `All` selects the whole step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteLegStepField {
+    All,
+    DistanceMeters,
+    StaticDuration,
+    Polyline(PolylineField),
+    StartLocation,
+    EndLocation,
+    NavigationInstruction,
+    TravelAdvisory,
+    LocalizedValues,
+    TransitDetails,
+    TravelMode,
+}
+
+impl RouteLegStepField {
+    fn child_path(&self) -> Option<String> {
+        Some(match self {
+            RouteLegStepField::All => return None,
+            RouteLegStepField::DistanceMeters => "distanceMeters".to_owned(),
+            RouteLegStepField::StaticDuration => "staticDuration".to_owned(),
+            RouteLegStepField::Polyline(field) => join("polyline", field.child_path()),
+            RouteLegStepField::StartLocation => "startLocation".to_owned(),
+            RouteLegStepField::EndLocation => "endLocation".to_owned(),
+            RouteLegStepField::NavigationInstruction => "navigationInstruction".to_owned(),
+            RouteLegStepField::TravelAdvisory => "travelAdvisory".to_owned(),
+            RouteLegStepField::LocalizedValues => "localizedValues".to_owned(),
+            RouteLegStepField::TransitDetails => "transitDetails".to_owned(),
+            RouteLegStepField::TravelMode => "travelMode".to_owned(),
+        })
+    }
+}
+
+/// Fields of `Polyline`. `All` selects the whole polyline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolylineField {
+    All,
+    EncodedPolyline,
+    GeoJsonLinestring,
+}
+
+impl PolylineField {
+    fn child_path(&self) -> Option<String> {
+        match self {
+            PolylineField::All => None,
+            PolylineField::EncodedPolyline => Some("encodedPolyline".to_owned()),
+            PolylineField::GeoJsonLinestring => Some("geoJsonLinestring".to_owned()),
+        }
+    }
+}
+
+/// Fields of `RouteTravelAdvisory`. `All` selects the whole advisory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteTravelAdvisoryField {
+    All,
+    TollInfo,
+    SpeedReadingIntervals,
+    FuelConsumptionMicroliters,
+    RouteRestrictionsPartiallyIgnored,
+    TransitFare,
+}
+
+impl RouteTravelAdvisoryField {
+    fn child_path(&self) -> Option<String> {
+        match self {
+            RouteTravelAdvisoryField::All => None,
+            RouteTravelAdvisoryField::TollInfo => Some("tollInfo".to_owned()),
+            RouteTravelAdvisoryField::SpeedReadingIntervals => Some("speedReadingIntervals".to_owned()),
+            RouteTravelAdvisoryField::FuelConsumptionMicroliters => Some("fuelConsumptionMicroliters".to_owned()),
+            RouteTravelAdvisoryField::RouteRestrictionsPartiallyIgnored => Some("routeRestrictionsPartiallyIgnored".to_owned()),
+            RouteTravelAdvisoryField::TransitFare => Some("transitFare".to_owned()),
+        }
+    }
+}
+
+/// Fields of `RouteLegTravelAdvisory`. `All` selects the whole advisory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteLegTravelAdvisoryField {
+    All,
+    TollInfo,
+    SpeedReadingIntervals,
+}
+
+impl RouteLegTravelAdvisoryField {
+    fn child_path(&self) -> Option<String> {
+        match self {
+            RouteLegTravelAdvisoryField::All => None,
+            RouteLegTravelAdvisoryField::TollInfo => Some("tollInfo".to_owned()),
+            RouteLegTravelAdvisoryField::SpeedReadingIntervals => Some("speedReadingIntervals".to_owned()),
+        }
+    }
+}
+
+/// Fields of `ComputeRouteMatrixResponse`, the elements of a route matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteMatrixElementField {
+    Status,
+    Condition,
+    DistanceMeters,
+    Duration,
+    StaticDuration,
+    TravelAdvisory(RouteTravelAdvisoryField),
+    FallbackInfo,
+    LocalizedValues,
+    OriginIndex,
+    DestinationIndex,
+}
+
+impl MaskField for RouteMatrixElementField {
+    fn path(&self) -> String {
+        match self {
+            RouteMatrixElementField::Status => "status".to_owned(),
+            RouteMatrixElementField::Condition => "condition".to_owned(),
+            RouteMatrixElementField::DistanceMeters => "distanceMeters".to_owned(),
+            RouteMatrixElementField::Duration => "duration".to_owned(),
+            RouteMatrixElementField::StaticDuration => "staticDuration".to_owned(),
+            RouteMatrixElementField::TravelAdvisory(field) => join("travelAdvisory", field.child_path()),
+            RouteMatrixElementField::FallbackInfo => "fallbackInfo".to_owned(),
+            RouteMatrixElementField::LocalizedValues => "localizedValues".to_owned(),
+            RouteMatrixElementField::OriginIndex => "originIndex".to_owned(),
+            RouteMatrixElementField::DestinationIndex => "destinationIndex".to_owned(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::services::route_service::get_route::response_model::ComputeRouteResponse;
+    use crate::services::route_service::get_route_matrix::response_model::ComputeRouteMatrixResponse;
+
+    #[test]
+    fn sku_of_masks() {
+        assert_eq!(RoutesSku::of_masks(None), RoutesSku::Enterprise);
+        assert_eq!(RoutesSku::of_masks(Some(&[])), RoutesSku::Essentials);
+        assert_eq!(RoutesSku::of_masks(Some(&["*"])), RoutesSku::Enterprise);
+        assert_eq!(RoutesSku::of_masks(Some(&["routes.duration", "routes.distanceMeters"])), RoutesSku::Essentials);
+        assert_eq!(RoutesSku::of_masks(Some(&["routes.duration", "routes.optimizedIntermediateWaypointIndex"])), RoutesSku::Pro);
+        assert_eq!(RoutesSku::of_masks(Some(&["routes.optimizedIntermediateWaypointIndex", "routes.travelAdvisory.tollInfo"])), RoutesSku::Enterprise);
+        assert_eq!(RoutesSku::of_masks(Some(&["originIndex", "travelAdvisory.routeRestrictionsPartiallyIgnored"])), RoutesSku::Pro);
+    }
+
+    #[test]
+    fn sku_of_parent_and_child_paths() {
+        // parents select the billed fields below them
+        assert_eq!(RoutesSku::of_path("routes"), RoutesSku::Enterprise);
+        assert_eq!(RoutesSku::of_path("routes.legs.travelAdvisory"), RoutesSku::Enterprise);
+        assert_eq!(RoutesSku::of_path(" routes.travelAdvisory.tollInfo.estimatedPrice "), RoutesSku::Enterprise);
+        // a common prefix is not a parent
+        assert_eq!(RoutesSku::of_path("routes.travelAdvisory.toll"), RoutesSku::Essentials);
+        assert_eq!(RoutesSku::of_path("routes.travelAdvisory.transitFare"), RoutesSku::Essentials);
+    }
+
+    #[test]
+    fn field_mask_paths() {
+        let mask = RoutesFieldMask::new()
+            .with(ComputeRoutesField::Routes(RouteField::Duration))
+            .with(ComputeRoutesField::Routes(RouteField::Legs(RouteLegField::Steps(RouteLegStepField::Polyline(PolylineField::EncodedPolyline)))))
+            .with(ComputeRoutesField::Routes(RouteField::Duration));
+        assert_eq!(mask.paths(), ["routes.duration", "routes.legs.steps.polyline.encodedPolyline"]);
+        assert_eq!(mask.to_string(), "routes.duration,routes.legs.steps.polyline.encodedPolyline");
+        assert_eq!(mask.sku(), RoutesSku::Essentials);
+
+        let mask = mask.with(ComputeRoutesField::Routes(RouteField::OptimizedIntermediateWaypointIndex));
+        assert_eq!(mask.sku(), RoutesSku::Pro);
+
+        let mask = RouteMatrixFieldMask::new().with(RouteMatrixElementField::TravelAdvisory(RouteTravelAdvisoryField::All));
+        assert_eq!(mask.paths(), ["travelAdvisory"]);
+        assert_eq!(mask.sku(), RoutesSku::Enterprise);
+    }
+    // Every variant of the field enums. The matches stop compiling when a variant is added, until it is listed here too.
+
+    fn polyline_fields() -> Vec<PolylineField> {
+        let _ = |field: PolylineField| match field { PolylineField::All | PolylineField::EncodedPolyline | PolylineField::GeoJsonLinestring => () };
+        vec![PolylineField::All, PolylineField::EncodedPolyline, PolylineField::GeoJsonLinestring]
+    }
+
+    fn route_travel_advisory_fields() -> Vec<RouteTravelAdvisoryField> {
+        use RouteTravelAdvisoryField::*;
+        let _ = |field: RouteTravelAdvisoryField| match field {
+            All | TollInfo | SpeedReadingIntervals | FuelConsumptionMicroliters | RouteRestrictionsPartiallyIgnored | TransitFare => (),
+        };
+        vec![All, TollInfo, SpeedReadingIntervals, FuelConsumptionMicroliters, RouteRestrictionsPartiallyIgnored, TransitFare]
+    }
+
+    fn route_leg_travel_advisory_fields() -> Vec<RouteLegTravelAdvisoryField> {
+        use RouteLegTravelAdvisoryField::*;
+        let _ = |field: RouteLegTravelAdvisoryField| match field { All | TollInfo | SpeedReadingIntervals => () };
+        vec![All, TollInfo, SpeedReadingIntervals]
+    }
+
+    fn route_leg_step_fields() -> Vec<RouteLegStepField> {
+        use RouteLegStepField::*;
+        let _ = |field: RouteLegStepField| match field {
+            All | DistanceMeters | StaticDuration | Polyline(_) | StartLocation | EndLocation | NavigationInstruction
+            | TravelAdvisory | LocalizedValues | TransitDetails | TravelMode => (),
+        };
+        let mut fields = vec![All, DistanceMeters, StaticDuration, StartLocation, EndLocation, NavigationInstruction, TravelAdvisory, LocalizedValues, TransitDetails, TravelMode];
+        fields.extend(polyline_fields().into_iter().map(Polyline));
+        fields
+    }
+
+    fn route_leg_fields() -> Vec<RouteLegField> {
+        use RouteLegField::*;
+        let _ = |field: RouteLegField| match field {
+            All | DistanceMeters | Duration | StaticDuration | Polyline(_) | StartLocation | EndLocation | Steps(_)
+            | TravelAdvisory(_) | LocalizedValues | StepsOverview => (),
+        };
+        let mut fields = vec![All, DistanceMeters, Duration, StaticDuration, StartLocation, EndLocation, LocalizedValues, StepsOverview];
+        fields.extend(polyline_fields().into_iter().map(Polyline));
+        fields.extend(route_leg_step_fields().into_iter().map(Steps));
+        fields.extend(route_leg_travel_advisory_fields().into_iter().map(TravelAdvisory));
+        fields
+    }
+
+    fn compute_routes_fields() -> Vec<ComputeRoutesField> {
+        use RouteField::*;
+        let _ = |field: RouteField| match field {
+            All | RouteLabels | Legs(_) | DistanceMeters | Duration | StaticDuration | Polyline(_) | Description | Warnings
+            | Viewport | TravelAdvisory(_) | OptimizedIntermediateWaypointIndex | LocalizedValues | RouteToken => (),
+        };
+        let _ = |field: ComputeRoutesField| match field {
+            ComputeRoutesField::Routes(_) | ComputeRoutesField::FallbackInfo | ComputeRoutesField::GeocodingResults => (),
+        };
+        let mut fields = vec![All, RouteLabels, DistanceMeters, Duration, StaticDuration, Description, Warnings, Viewport, OptimizedIntermediateWaypointIndex, LocalizedValues, RouteToken];
+        fields.extend(route_leg_fields().into_iter().map(Legs));
+        fields.extend(polyline_fields().into_iter().map(Polyline));
+        fields.extend(route_travel_advisory_fields().into_iter().map(TravelAdvisory));
+        let mut fields: Vec<ComputeRoutesField> = fields.into_iter().map(ComputeRoutesField::Routes).collect();
+        fields.extend([ComputeRoutesField::FallbackInfo, ComputeRoutesField::GeocodingResults]);
+        fields
+    }
+
+    fn route_matrix_element_fields() -> Vec<RouteMatrixElementField> {
+        use RouteMatrixElementField::*;
+        let _ = |field: RouteMatrixElementField| match field {
+            Status | Condition | DistanceMeters | Duration | StaticDuration | TravelAdvisory(_) | FallbackInfo | LocalizedValues
+            | OriginIndex | DestinationIndex => (),
+        };
+        let mut fields = vec![Status, Condition, DistanceMeters, Duration, StaticDuration, FallbackInfo, LocalizedValues, OriginIndex, DestinationIndex];
+        fields.extend(route_travel_advisory_fields().into_iter().map(TravelAdvisory));
+        fields
+    }
+
+    fn localized_text(text: &str) -> Value {
+        json!({ "text": text, "languageCode": "en-US" })
+    }
+
+    fn location() -> Value {
+        json!({ "latLng": { "latitude": 1.5, "longitude": 2.5 }, "heading": 90 })
+    }
+
+    fn polyline() -> Value {
+        json!({ "encodedPolyline": "_p~iF~ps|U", "geoJsonLinestring": { "type": "LineString", "coordinates": [[2.5, 1.5]] } })
+    }
+
+    fn navigation_instruction() -> Value {
+        json!({ "maneuver": "TURN_LEFT", "instructions": "Turn left" })
+    }
+
+    fn money() -> Value {
+        json!({ "currencyCode": "USD", "units": "3", "nanos": 500000000 })
+    }
+
+    fn speed_reading_interval() -> Value {
+        json!({ "startPolylinePointIndex": 0, "endPolylinePointIndex": 1, "speed": "NORMAL" })
+    }
+
+    fn route_travel_advisory() -> Value {
+        json!({
+            "tollInfo": { "estimatedPrice": [money()] },
+            "speedReadingIntervals": speed_reading_interval(),
+            "fuelConsumptionMicroliters": "1000",
+            "routeRestrictionsPartiallyIgnored": true,
+            "transitFare": money(),
+        })
+    }
+
+    fn route_localized_values() -> Value {
+        json!({ "distance": localized_text("1 km"), "duration": localized_text("2 mins"), "staticDuration": localized_text("1 min"), "transitFare": localized_text("$3.50") })
+    }
+
+    fn fallback_info() -> Value {
+        json!({ "routingMode": "FALLBACK_TRAFFIC_UNAWARE", "reason": "SERVER_ERROR" })
+    }
+
+    fn status() -> Value {
+        json!({ "code": 5, "message": "not found", "details": [{ "@type": "type.googleapis.com/google.rpc.ErrorInfo" }] })
+    }
+
+    /// Response setting every field of the response models.
+    fn compute_routes_response() -> Value {
+        let transit_stop = json!({ "name": "Central", "location": location() });
+        let localized_time = json!({ "time": localized_text("10:00"), "timeZone": "America/Los_Angeles" });
+        let step = json!({
+            "distanceMeters": 100,
+            "staticDuration": "60s",
+            "polyline": polyline(),
+            "startLocation": location(),
+            "endLocation": location(),
+            "navigationInstruction": navigation_instruction(),
+            "travelAdvisory": { "speedReadingIntervals": [speed_reading_interval()] },
+            "localizedValues": { "distance": localized_text("100 m"), "staticDuration": localized_text("1 min") },
+            "transitDetails": {
+                "stopDetails": { "arrivalStop": transit_stop, "arrivalTime": "2024-01-01T10:05:00Z", "departureStop": transit_stop, "departureTime": "2024-01-01T10:00:00Z" },
+                "localizedValues": { "arrivalTime": localized_time, "departureTime": localized_time },
+                "headsign": "Downtown",
+                "headway": "600s",
+                "transitLine": {
+                    "agencies": [{ "name": "Transit", "phoneNumber": "+1 555-0100", "uri": "https://transit.example.com" }],
+                    "name": "Line 1",
+                    "uri": "https://transit.example.com/1",
+                    "color": "#ff0000",
+                    "iconUri": "https://transit.example.com/1.png",
+                    "nameShort": "1",
+                    "textColor": "#ffffff",
+                    "vehicle": { "name": localized_text("Bus"), "type": "BUS", "iconUri": "https://transit.example.com/bus.png", "localIconUri": "https://transit.example.com/local-bus.png" },
+                },
+                "stopCount": 2,
+                "tripShortText": "101",
+            },
+            "travelMode": "TRANSIT",
+        });
+        let leg = json!({
+            "distanceMeters": 1000,
+            "duration": "120s",
+            "staticDuration": "60s",
+            "polyline": polyline(),
+            "startLocation": location(),
+            "endLocation": location(),
+            "steps": [step],
+            "travelAdvisory": { "tollInfo": { "estimatedPrice": [money()] }, "speedReadingIntervals": [speed_reading_interval()] },
+            "localizedValues": { "distance": localized_text("1 km"), "duration": localized_text("2 mins"), "staticDuration": localized_text("1 min") },
+            "stepsOverview": { "multiModalSegments": [{ "navigationInstruction": navigation_instruction(), "travelMode": "WALK", "stepStartIndex": 0, "stepEndIndex": 1 }] },
+        });
+        let geocoded_waypoint = json!({
+            "geocoderStatus": status(),
+            "type": ["street_address"],
+            "partialMatch": true,
+            "placeId": ["ChIJ"],
+            "intermediateWaypointRequestIndex": 0,
+        });
+        json!({
+            "routes": [{
+                "routeLabels": ["DEFAULT_ROUTE"],
+                "legs": [leg],
+                "distanceMeters": 1000,
+                "duration": "120s",
+                "staticDuration": "60s",
+                "polyline": polyline(),
+                "description": "Main St",
+                "warnings": ["Tolls"],
+                "viewport": { "low": { "latitude": 1.5, "longitude": 2.5 }, "high": { "latitude": 3.5, "longitude": 4.5 } },
+                "travelAdvisory": route_travel_advisory(),
+                "optimizedIntermediateWaypointIndex": [0],
+                "localizedValues": route_localized_values(),
+                "routeToken": "token",
+            }],
+            "fallbackInfo": fallback_info(),
+            "geocodingResults": { "origin": geocoded_waypoint, "destination": geocoded_waypoint, "intermediates": [geocoded_waypoint] },
+        })
+    }
+
+    /// Element setting every field of the response model.
+    fn route_matrix_element() -> Value {
+        json!({
+            "status": status(),
+            "condition": "ROUTE_EXISTS",
+            "distanceMeters": 1000,
+            "duration": "120s",
+            "staticDuration": "60s",
+            "travelAdvisory": route_travel_advisory(),
+            "fallbackInfo": fallback_info(),
+            "localizedValues": route_localized_values(),
+            "originIndex": 1,
+            "destinationIndex": 2,
+        })
+    }
+
+    // paths of the values of a response, through the items of lists
+    fn value_paths(value: &Value, path: &str, paths: &mut Vec<String>) {
+        let child = |key: &str| if path.is_empty() { key.to_owned() } else { format!("{}.{}", path, key) };
+        match value {
+            Value::Object(object) => object.iter().for_each(|(key, value)| value_paths(value, &child(key), paths)),
+            Value::Array(items) => items.iter().for_each(|item| value_paths(item, path, paths)),
+            _ => paths.push(path.to_owned()),
+        }
+    }
+
+    /// Check that every path of the mask enums selects a field of the response model, and that every field of the model
+    /// is selected by a path other than its parents.
+    fn check_paths(response: &Value, mask_paths: &[String]) {
+        let mut model_paths = vec![];
+        value_paths(response, "", &mut model_paths);
+
+        for mask_path in mask_paths {
+            assert!(model_paths.iter().any(|path| path == mask_path || path.starts_with(&format!("{}.", mask_path))), "no field of the model at {}", mask_path);
+        }
+
+        let leaf_mask_paths: Vec<&String> = mask_paths.iter().filter(|mask_path| !mask_paths.iter().any(|path| path.starts_with(&format!("{}.", mask_path)))).collect();
+        for model_path in &model_paths {
+            assert!(leaf_mask_paths.iter().any(|mask_path| model_path == *mask_path || model_path.starts_with(&format!("{}.", mask_path))), "no field mask path for {}", model_path);
+        }
+    }
+
+    #[test]
+    fn compute_routes_paths_match_the_response_model() -> Result<(), serde_json::Error> {
+        let response = compute_routes_response();
+        let model = serde_json::to_value(serde_json::from_value::<ComputeRouteResponse>(response.clone())?)?;
+        // the fixture sets only fields of the model
+        assert_eq!(model, response);
+
+        let mask_paths: Vec<String> = compute_routes_fields().iter().map(MaskField::path).collect();
+        check_paths(&model, &mask_paths);
+        Ok(())
+    }
+
+    #[test]
+    fn route_matrix_paths_match_the_response_model() -> Result<(), serde_json::Error> {
+        let element = route_matrix_element();
+        let model = serde_json::to_value(serde_json::from_value::<ComputeRouteMatrixResponse>(element.clone())?)?;
+        assert_eq!(model, element);
+
+        let mask_paths: Vec<String> = route_matrix_element_fields().iter().map(MaskField::path).collect();
+        check_paths(&model, &mask_paths);
+        Ok(())
+    }
+}
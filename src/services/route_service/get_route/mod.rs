@@ -7,11 +7,15 @@ use std::collections::HashMap;
 use super::common_models::WayPoint;
 use request_model::ComputeRouteRequest;
 use anyhow::{Ok, Result};
-use reqwest::{header::HeaderValue, Client, Url};
+use reqwest::{header::{HeaderMap, HeaderValue}, Method};
 use response_model::ComputeRouteResponse;
 use serde_json::Value;
 
-use super::{RouteService, GET_ROUTE_URL};
+use super::{RouteService, GET_ROUTE_PATH};
+use crate::services::ApiMethod;
+use crate::services::request_options::RequestOptions;
+
+static GET_ROUTE: ApiMethod = ApiMethod { service: "routes", name: "get_route", idempotent: true };
 
 impl RouteService {
 
@@ -20,11 +24,12 @@ impl RouteService {
     ///
     /// * `origin` -  Origin waypoint.
     /// * `destination` -  destination waypoint.
-    /// * `response_masks` - response field mask. If not specified, all available fields will be included.<br>
-    ///     Example: vec!["routes.duration", "routes.distanceMeters"] to return only distanceMeters and duration field for the route.
-    ///     See https://developers.google.com/maps/documentation/routes/choose_fields for more details.
+    /// * `response_masks` - response field mask. If not specified, all available fields will be included, which is billed as the Enterprise SKU.<br>
+    ///   Build it with a [FieldMask](crate::services::route_service::field_mask::FieldMask) to check the paths at compile time and see the SKU they trigger.<br>
+    ///   Example: vec!["routes.duration", "routes.distanceMeters"] to return only distanceMeters and duration field for the route.
+    ///   See https://developers.google.com/maps/documentation/routes/choose_fields for more details.
     /// * `params` - Optional Additional Parameter. Keys accepted are the following.
-    ///    See [ComputeRouteRequestOptinalParams](ComputeRouteRequestOptinalParams) for type detail
+    ///   See [ComputeRouteRequestOptinalParams](ComputeRouteRequestOptinalParams) for type detail
     ///     * `intermediates`
     ///     * `travelMode`
     ///     * `routingPreference`
@@ -43,27 +48,43 @@ impl RouteService {
     ///     * `trafficModel`
     ///     * `transitPreferences`
     pub async fn get_route(&mut self, origin: &WayPoint, destination: &WayPoint, response_masks: Option<Vec<&str>>, params: Option<HashMap<String, Value>>) -> Result<ComputeRouteResponse>{
+        self.get_route_with_options(origin, destination, response_masks, params, &RequestOptions::default()).await
+    }
+
+    /// Get a route, with per-request options such as a timeout. <br>
+    /// Arguments are the same as `get_route`.
+    ///
+    /// * `options` -  `RequestOptions` of the call.
+    #[tracing::instrument(skip_all, fields(method = "get_route", endpoint, status, latency_ms, retry_count, response_size))]
+    pub async fn get_route_with_options(&mut self, origin: &WayPoint, destination: &WayPoint, response_masks: Option<Vec<&str>>, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<ComputeRouteResponse>{
+        options.run(async {
+
+            let base_url = self.url(GET_ROUTE_PATH)?;
+            let mut headers = HeaderMap::new();
+            options.apply_headers(&mut headers)?;
 
-        let base_url = Url::parse(&format!("{}", GET_ROUTE_URL))?;
-        let mut headers = self.base.create_headers().await?;
+            // add field mask
+            let mask_string = if let Some(masks) = response_masks {
+                masks.join(",")
+            } else {
+                "*".to_owned()
+            };
+            headers.insert("X-Goog-FieldMask", HeaderValue::from_str(&mask_string)?);
 
-        // add field mask
-        let mask_string = if let Some(masks) = response_masks {
-            masks.join(",")
-        } else {
-            "*".to_owned()
-        };
-        headers.insert("X-Goog-FieldMask", HeaderValue::from_str(&mask_string)?);
+            let request_body = ComputeRouteRequest::new(origin, destination, params)?;
 
-        let request_body = ComputeRouteRequest::new(origin, destination, params)?;
+            #[cfg(feature = "grpc")]
+            if self.grpc.is_some() {
+                self.base.add_auth_headers(&mut headers).await?;
+                return self.compute_routes_grpc(&serde_json::to_value(&request_body)?, headers, &GET_ROUTE).await;
+            }
 
-        let builder: reqwest::RequestBuilder = Client::new().post(base_url)
-                .headers(headers)
-                .body(serde_json::to_string(&request_body)?);
+            let request = self.base.build_request(Method::POST, base_url, headers, serde_json::to_vec(&request_body)?)?;
 
-        let body = self.base.make_request(builder).await?;
+            let body = self.base.make_cached_request(request, &GET_ROUTE, 0).await?;
 
-        Ok(serde_json::from_str::<ComputeRouteResponse>(&body)?)
+            Ok(serde_json::from_str::<ComputeRouteResponse>(&body)?)
 
+        }).await
     }
 }
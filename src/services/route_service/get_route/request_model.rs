@@ -33,11 +33,11 @@ impl ComputeRouteRequest {
             None
         };
 
-        return Ok(Self{
+        Ok(Self{
             origin: origin.to_owned(),
             destination: destination.to_owned(),
             params: additional_params
-        });
+        })
     }
 }
 
@@ -4,11 +4,17 @@ pub mod response_model;
 use std::collections::HashMap;
 use anyhow::{Ok, Result};
 use request_model::{ComputeRouteMatrixRequest, RouteMatrixOrigin};
-use reqwest::{header::HeaderValue, Client, Url};
+use reqwest::{header::{HeaderMap, HeaderValue}, Method};
 use response_model::ComputeRouteMatrixResponse;
 use serde_json::Value;
+#[cfg(feature = "grpc")]
+use futures::{Stream, StreamExt};
 
-use super::{RouteService, GET_ROUTE_MATRIX_URL};
+use super::{RouteService, GET_ROUTE_MATRIX_PATH};
+use crate::services::ApiMethod;
+use crate::services::request_options::RequestOptions;
+
+static GET_ROUTE_MATRIX: ApiMethod = ApiMethod { service: "routes", name: "get_route_matrix", idempotent: true };
 
 impl RouteService {
 
@@ -17,11 +23,12 @@ impl RouteService {
     ///
     /// * `origin` -  Origin waypoint.
     /// * `destination` -  destination waypoint.
-    /// * `response_masks` - response field mask. If not specified, all available fields will be included.<br>
-    ///     Example: vec!["routes.duration", "routes.distanceMeters"] to return only distanceMeters and duration field for the route.
-    ///     See https://developers.google.com/maps/documentation/routes/choose_fields for more details.
+    /// * `response_masks` - response field mask. If not specified, all available fields will be included, which is billed as the Enterprise SKU.<br>
+    ///   Build it with a [FieldMask](crate::services::route_service::field_mask::FieldMask) to check the paths at compile time and see the SKU they trigger.<br>
+    ///   Example: vec!["routes.duration", "routes.distanceMeters"] to return only distanceMeters and duration field for the route.
+    ///   See https://developers.google.com/maps/documentation/routes/choose_fields for more details.
     /// * `params` - Optional Additional Parameter. Keys accepted are the following.
-    ///    See [ComputeRouteMatixRequestOptinalParams](ComputeRouteMatixRequestOptinalParams) for type detail
+    ///   See [ComputeRouteMatixRequestOptinalParams](ComputeRouteMatixRequestOptinalParams) for type detail
     ///     * `intermediates`
     ///     * `travelMode`
     ///     * `routingPreference`
@@ -34,28 +41,77 @@ impl RouteService {
     ///     * `trafficModel`
     ///     * `transitPreferences`
     pub async fn get_route_matrix(&mut self, origin: &Vec<RouteMatrixOrigin>, destination: &Vec<RouteMatrixOrigin>, response_masks: Option<Vec<&str>>, params: Option<HashMap<String, Value>>) -> Result<Vec<ComputeRouteMatrixResponse>>{
+        self.get_route_matrix_with_options(origin, destination, response_masks, params, &RequestOptions::default()).await
+    }
+
+    /// Calculate the distance and duration of a route for multiple origins and destinations, with per-request options such as a timeout. <br>
+    /// Arguments are the same as `get_route_matrix`.
+    ///
+    /// * `options` -  `RequestOptions` of the call.
+    #[tracing::instrument(skip_all, fields(method = "get_route_matrix", endpoint, status, latency_ms, retry_count, response_size))]
+    pub async fn get_route_matrix_with_options(&mut self, origin: &Vec<RouteMatrixOrigin>, destination: &Vec<RouteMatrixOrigin>, response_masks: Option<Vec<&str>>, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<Vec<ComputeRouteMatrixResponse>>{
+        options.run(async {
+
+            let base_url = self.url(GET_ROUTE_MATRIX_PATH)?;
+            let mut headers = HeaderMap::new();
+            options.apply_headers(&mut headers)?;
+
+            // add field mask
+            let mask_string = if let Some(masks) = response_masks {
+                masks.join(",")
+            } else {
+                "*".to_owned()
+            };
 
-        let base_url = Url::parse(&format!("{}", GET_ROUTE_MATRIX_URL))?;
-        let mut headers = self.base.create_headers().await?;
+            headers.insert("X-Goog-FieldMask", HeaderValue::from_str(&mask_string)?);
 
-        // add field mask
-        let mask_string = if let Some(masks) = response_masks {
-            masks.join(",")
-        } else {
-            "*".to_owned()
-        };
+            let request_body = ComputeRouteMatrixRequest::new(origin, destination, params)?;
 
-        headers.insert("X-Goog-FieldMask", HeaderValue::from_str(&mask_string)?);
+            #[cfg(feature = "grpc")]
+            if self.grpc.is_some() {
+                self.base.add_auth_headers(&mut headers).await?;
+                let elements = self.compute_route_matrix_grpc(&serde_json::to_value(&request_body)?, headers, &GET_ROUTE_MATRIX).await?;
+                return elements.collect::<Vec<_>>().await.into_iter().collect();
+            }
 
-        let request_body = ComputeRouteMatrixRequest::new(origin, destination, params)?;
+            let request = self.base.build_request(Method::POST, base_url, headers, serde_json::to_vec(&request_body)?)?;
 
-        let builder: reqwest::RequestBuilder = Client::new().post(base_url)
-                .headers(headers)
-                .body(serde_json::to_string(&request_body)?);
+            let body = self.base.make_cached_request(request, &GET_ROUTE_MATRIX, 0).await?;
 
-        let body = self.base.make_request(builder).await?;
+            Ok(serde_json::from_str::<Vec<ComputeRouteMatrixResponse>>(&body)?)
 
-        Ok(serde_json::from_str::<Vec<ComputeRouteMatrixResponse>>(&body)?)
+        }).await
+    }
+
+    /// Calculate the distance and duration of routes for multiple origins and destinations,
+    /// streaming the elements as the server computes them. Requires gRPC, see `with_grpc`. <br>
+    /// See https://developers.google.com/maps/documentation/routes/compute_route_matrix
+    ///
+    /// Arguments are the same as `get_route_matrix`.
+    #[cfg(feature = "grpc")]
+    pub async fn get_route_matrix_stream(&mut self, origin: &Vec<RouteMatrixOrigin>, destination: &Vec<RouteMatrixOrigin>, response_masks: Option<Vec<&str>>, params: Option<HashMap<String, Value>>) -> Result<impl Stream<Item = Result<ComputeRouteMatrixResponse>>> {
+        self.get_route_matrix_stream_with_options(origin, destination, response_masks, params, &RequestOptions::default()).await
+    }
+
+    /// Stream the elements of a route matrix, with per-request options such as a timeout. Requires gRPC, see `with_grpc`. <br>
+    /// The timeout and cancellation token also cover reading the stream, which yields the `RequestError` and ends when they interrupt it.
+    /// Arguments are the same as `get_route_matrix`.
+    ///
+    /// * `options` -  `RequestOptions` of the call.
+    #[cfg(feature = "grpc")]
+    #[tracing::instrument(skip_all, fields(method = "get_route_matrix_stream", endpoint, status, latency_ms, retry_count))]
+    pub async fn get_route_matrix_stream_with_options(&mut self, origin: &Vec<RouteMatrixOrigin>, destination: &Vec<RouteMatrixOrigin>, response_masks: Option<Vec<&str>>, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<impl Stream<Item = Result<ComputeRouteMatrixResponse>>> {
+        let started = tokio::time::Instant::now();
+        let elements = options.run(async {
+            let mut headers = HeaderMap::new();
+            options.apply_headers(&mut headers)?;
+            let mask_string = response_masks.map(|masks| masks.join(",")).unwrap_or("*".to_owned());
+            headers.insert("X-Goog-FieldMask", HeaderValue::from_str(&mask_string)?);
+            self.base.add_auth_headers(&mut headers).await?;
 
+            let request_body = ComputeRouteMatrixRequest::new(origin, destination, params)?;
+            self.compute_route_matrix_grpc(&serde_json::to_value(&request_body)?, headers, &GET_ROUTE_MATRIX).await
+        }).await?;
+        Ok(options.run_stream(started, elements))
     }
 }
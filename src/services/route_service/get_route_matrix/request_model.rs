@@ -40,11 +40,11 @@ impl ComputeRouteMatrixRequest {
             None
         };
 
-        return Ok(Self{
+        Ok(Self{
             origins: origin.to_owned(),
             destinations: destination.to_owned(),
             params: additional_params
-        });
+        })
     }
 }
 
@@ -0,0 +1,647 @@
+//! gRPC transport of `RouteService`, enabled with the `grpc` feature and `RouteService::with_grpc`.
+//!
+//! Requests are built from the same request models as the REST transport, and responses are mapped to the same
+//! response models, so the transport can be switched without changing the calling code.
+
+pub mod proto;
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+use futures::{Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use serde_json::{json, Map, Value};
+use tokio::sync::OnceCell;
+use tokio::time;
+use tonic::codec::ProstCodec;
+use tonic::metadata::MetadataMap;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tracing::Span;
+
+use super::get_route::response_model::ComputeRouteResponse;
+use super::get_route_matrix::response_model::ComputeRouteMatrixResponse;
+use super::RouteService;
+use crate::metrics;
+use crate::services::service_error::ServiceError;
+use crate::services::ApiMethod;
+use crate::services::mtls::ClientCertificate;
+use crate::services::retry_policy;
+
+static COMPUTE_ROUTES_PATH: &str = "/google.maps.routing.v2.Routes/ComputeRoutes";
+static COMPUTE_ROUTE_MATRIX_PATH: &str = "/google.maps.routing.v2.Routes/ComputeRouteMatrix";
+static FIELD_MASK_METADATA: &str = "x-goog-fieldmask";
+
+// Fields of the REST request models carried by the messages in `proto`. Requests setting any other field are rejected
+// instead of being sent without it, since the REST API would honor it.
+static COMPUTE_ROUTES_FIELDS: [&str; 11] = [
+    "origin", "destination", "intermediates", "travelMode", "routingPreference", "departureTime", "arrivalTime",
+    "computeAlternativeRoutes", "languageCode", "regionCode", "units",
+];
+static COMPUTE_ROUTE_MATRIX_FIELDS: [&str; 9] = [
+    "origins", "destinations", "travelMode", "routingPreference", "departureTime", "arrivalTime", "languageCode", "regionCode", "units",
+];
+static ROUTE_MATRIX_ORIGIN_FIELDS: [&str; 1] = ["waypoint"];
+static WAYPOINT_FIELDS: [&str; 6] = ["location", "placeId", "address", "via", "vehicleStopover", "sideOfRoad"];
+static LOCATION_FIELDS: [&str; 2] = ["latLng", "heading"];
+
+// Response paths filled by the mapping to the REST response models, and their subfields. Field masks naming any other
+// path are rejected instead of coming back empty, and `*` is narrowed to these paths.
+static ROUTE_PATHS: [&str; 16] = [
+    "routes.routeLabels",
+    "routes.legs.distanceMeters",
+    "routes.legs.duration",
+    "routes.legs.staticDuration",
+    "routes.legs.polyline",
+    "routes.legs.startLocation",
+    "routes.legs.endLocation",
+    "routes.distanceMeters",
+    "routes.duration",
+    "routes.staticDuration",
+    "routes.polyline",
+    "routes.description",
+    "routes.warnings",
+    "routes.viewport",
+    "routes.optimizedIntermediateWaypointIndex",
+    "routes.routeToken",
+];
+static ROUTE_MATRIX_ELEMENT_PATHS: [&str; 7] = [
+    "originIndex", "destinationIndex", "status", "condition", "distanceMeters", "duration", "staticDuration",
+];
+
+
+/// Lazily connected gRPC channel, shared by clones of the service.
+/// The channel keeps the endpoint and client certificate it was connected with, so the service
+/// replaces it with a new `GrpcChannel` when they change.
+#[derive(Debug, Clone, Default)]
+pub(super) struct GrpcChannel {
+    channel: Arc<OnceCell<Channel>>,
+}
+
+impl GrpcChannel {
+    async fn get(&self, endpoint: &reqwest::Url, client_certificate: Option<&ClientCertificate>) -> Result<Channel> {
+        let channel = self.channel.get_or_try_init(|| async {
+            let mut channel_endpoint = Endpoint::from_shared(endpoint.as_str().trim_end_matches('/').to_owned())?;
+            if endpoint.scheme() == "https" {
+                let domain = endpoint.host_str().ok_or(anyhow!("Endpoint has no host!"))?;
+                let mut tls_config = ClientTlsConfig::new().domain_name(domain);
+                if let Some(client_certificate) = client_certificate {
+                    tls_config = tls_config.identity(client_certificate.tonic_identity());
+                }
+                channel_endpoint = channel_endpoint.tls_config(tls_config)?;
+            }
+            Ok::<_, anyhow::Error>(channel_endpoint.connect_lazy())
+        }).await?;
+        Ok(channel.clone())
+    }
+}
+
+
+impl RouteService {
+    /// Send a `ComputeRoutes` request over gRPC.
+    pub(super) async fn compute_routes_grpc(&mut self, request_body: &Value, headers: HeaderMap, method: &ApiMethod) -> Result<ComputeRouteResponse> {
+        let request = compute_routes_request(request_body)?;
+        let (grpc, metadata) = self.grpc_client(headers, &ROUTE_PATHS).await?;
+
+        let started = Instant::now();
+        let result = self.call_with_retries(method, || {
+            let mut grpc = grpc.clone();
+            let mut request = tonic::Request::new(request.clone());
+            *request.metadata_mut() = metadata.clone();
+            async move {
+                grpc.ready().await.map_err(|error| tonic::Status::unavailable(format!("gRPC channel not ready: {}", error)))?;
+                grpc.unary(request, http::uri::PathAndQuery::from_static(COMPUTE_ROUTES_PATH), ProstCodec::<proto::ComputeRoutesRequest, proto::ComputeRoutesResponse>::default()).await
+            }
+        }).await;
+        record_call(method, result.as_ref().err(), started);
+
+        let response = result.map_err(service_error)?.into_inner();
+        let routes: Vec<Value> = response.routes.iter().map(route_json).collect();
+        Ok(serde_json::from_value(json!({ "routes": routes }))?)
+    }
+
+    /// Send a `ComputeRouteMatrix` request over gRPC, and return the elements as the server streams them.
+    /// Only the call is retried, not errors in the middle of the stream.
+    pub(super) async fn compute_route_matrix_grpc(&mut self, request_body: &Value, headers: HeaderMap, method: &ApiMethod) -> Result<impl Stream<Item = Result<ComputeRouteMatrixResponse>>> {
+        let request = compute_route_matrix_request(request_body)?;
+        let (grpc, metadata) = self.grpc_client(headers, &ROUTE_MATRIX_ELEMENT_PATHS).await?;
+
+        let started = Instant::now();
+        let result = self.call_with_retries(method, || {
+            let mut grpc = grpc.clone();
+            let mut request = tonic::Request::new(request.clone());
+            *request.metadata_mut() = metadata.clone();
+            async move {
+                grpc.ready().await.map_err(|error| tonic::Status::unavailable(format!("gRPC channel not ready: {}", error)))?;
+                grpc.server_streaming(request, http::uri::PathAndQuery::from_static(COMPUTE_ROUTE_MATRIX_PATH), ProstCodec::<proto::ComputeRouteMatrixRequest, proto::RouteMatrixElement>::default()).await
+            }
+        }).await;
+        record_call(method, result.as_ref().err(), started);
+
+        let elements = result.map_err(service_error)?.into_inner();
+        Ok(elements.map(|element| {
+            let element = element.map_err(service_error)?;
+            Ok(serde_json::from_value(route_matrix_element_json(&element))?)
+        }))
+    }
+
+    /// Connect the channel, and convert the headers of the REST request to gRPC metadata.
+    /// The field mask may only name the response paths the call maps, in `supported_paths`.
+    async fn grpc_client(&mut self, mut headers: HeaderMap, supported_paths: &[&str]) -> Result<(tonic::client::Grpc<Channel>, MetadataMap)> {
+        let Some(grpc_channel) = self.grpc.clone() else {
+            return Err(anyhow!("gRPC is not enabled for this service! Call with_grpc first."));
+        };
+        let endpoint = self.url("")?;
+        let channel = grpc_channel.get(&endpoint, self.base.client_certificate.as_ref()).await?;
+        Span::current().record("endpoint", endpoint.as_str());
+
+        headers.remove(CONTENT_TYPE);
+        if let Some(field_mask) = headers.get(FIELD_MASK_METADATA).and_then(|field_mask| field_mask.to_str().ok()) {
+            let field_mask = HeaderValue::from_str(&grpc_field_mask(&supported_field_mask(field_mask, supported_paths)?))?;
+            headers.insert(FIELD_MASK_METADATA, field_mask);
+        }
+
+        Ok((tonic::client::Grpc::new(channel), MetadataMap::from_headers(headers)))
+    }
+
+    /// Make a call, retrying the statuses the REST transport retries according to the retry policy:
+    /// `RESOURCE_EXHAUSTED`, `UNAVAILABLE` and `DEADLINE_EXCEEDED`, as for 429, 503 and 504 responses.
+    /// Each attempt waits for the rate limiter, if any.
+    async fn call_with_retries<T, F, Fut>(&self, method: &ApiMethod, mut call: F) -> Result<T, tonic::Status>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, tonic::Status>>,
+    {
+        let retry_policy = &self.base.retry_policy;
+        let max_attempts = if method.idempotent || retry_policy.retries_non_idempotent() {
+            retry_policy.max_attempts()
+        } else {
+            1
+        };
+
+        let mut attempt: u32 = 1;
+        loop {
+            if let Some(rate_limiter) = &self.base.rate_limiter {
+                rate_limiter.acquire_query().await;
+            }
+
+            match call().await {
+                Err(status) if attempt < max_attempts && is_retryable(&status) => {
+                    let delay = retry_policy.backoff(attempt);
+                    tracing::debug!(code = ?status.code(), delay_ms = delay.as_millis() as u64, "retrying gRPC call");
+                    time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => {
+                    Span::current().record("retry_count", attempt - 1);
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+
+fn is_retryable(status: &tonic::Status) -> bool {
+    let http_status = ServiceError::from_rpc_status(status.code() as i32, "", vec![]).http_status();
+    http::StatusCode::from_u16(http_status).is_ok_and(retry_policy::is_retryable_status)
+}
+
+fn record_call(method: &ApiMethod, error: Option<&tonic::Status>, started: Instant) {
+    let http_status = error.map_or(200, |status| ServiceError::from_rpc_status(status.code() as i32, "", vec![]).http_status());
+    let span = Span::current();
+    span.record("status", http_status);
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    metrics::record_request(method.service, method.name, http_status, started.elapsed());
+}
+
+/// Map a gRPC status to a `ServiceError`, like the REST transport does for error responses.
+fn service_error(status: tonic::Status) -> anyhow::Error {
+    ServiceError::from_rpc_status(status.code() as i32, status.message(), vec![]).into()
+}
+
+/// Check that the paths of the field mask are mapped, and replace `*` with the mapped paths.
+fn supported_field_mask(field_mask: &str, supported_paths: &[&str]) -> Result<String> {
+    let mut paths = vec![];
+    for path in field_mask.split(',').map(str::trim).filter(|path| !path.is_empty()) {
+        if path == "*" {
+            paths.extend_from_slice(supported_paths);
+            continue;
+        }
+        let is_supported = supported_paths
+            .iter()
+            .any(|supported| path.strip_prefix(supported).is_some_and(|rest| rest.is_empty() || rest.starts_with('.')));
+        if !is_supported {
+            return Err(anyhow!("Field mask path {} is not supported over gRPC! Supported paths are {}.", path, supported_paths.join(", ")));
+        }
+        paths.push(path);
+    }
+    Ok(paths.join(","))
+}
+
+/// gRPC field masks use the proto field names, such as `routes.distance_meters` for `routes.distanceMeters`.
+fn grpc_field_mask(field_mask: &str) -> String {
+    field_mask
+        .chars()
+        .flat_map(|c| match c.is_ascii_uppercase() {
+            true => vec!['_', c.to_ascii_lowercase()],
+            false => vec![c],
+        })
+        .collect()
+}
+
+
+// Requests are converted from the JSON of the REST request models.
+
+fn compute_routes_request(body: &Value) -> Result<proto::ComputeRoutesRequest> {
+    check_fields(body, &COMPUTE_ROUTES_FIELDS, "ComputeRoutes request")?;
+    Ok(proto::ComputeRoutesRequest {
+        origin: Some(waypoint(&body["origin"])?),
+        destination: Some(waypoint(&body["destination"])?),
+        intermediates: body["intermediates"].as_array().into_iter().flatten().map(waypoint).collect::<Result<_>>()?,
+        travel_mode: travel_mode(&body["travelMode"]) as i32,
+        routing_preference: routing_preference(&body["routingPreference"]) as i32,
+        departure_time: timestamp(&body["departureTime"])?,
+        arrival_time: timestamp(&body["arrivalTime"])?,
+        compute_alternative_routes: body["computeAlternativeRoutes"].as_bool().unwrap_or_default(),
+        language_code: body["languageCode"].as_str().unwrap_or_default().to_owned(),
+        region_code: body["regionCode"].as_str().unwrap_or_default().to_owned(),
+        units: units(&body["units"]) as i32,
+    })
+}
+
+fn compute_route_matrix_request(body: &Value) -> Result<proto::ComputeRouteMatrixRequest> {
+    check_fields(body, &COMPUTE_ROUTE_MATRIX_FIELDS, "ComputeRouteMatrix request")?;
+    let waypoints = |value: &Value| -> Result<Vec<proto::Waypoint>> {
+        value.as_array().into_iter().flatten().map(|origin| {
+            check_fields(origin, &ROUTE_MATRIX_ORIGIN_FIELDS, "route matrix origin or destination")?;
+            waypoint(&origin["waypoint"])
+        }).collect()
+    };
+    Ok(proto::ComputeRouteMatrixRequest {
+        origins: waypoints(&body["origins"])?.into_iter().map(|waypoint| proto::RouteMatrixOrigin { waypoint: Some(waypoint) }).collect(),
+        destinations: waypoints(&body["destinations"])?.into_iter().map(|waypoint| proto::RouteMatrixDestination { waypoint: Some(waypoint) }).collect(),
+        travel_mode: travel_mode(&body["travelMode"]) as i32,
+        routing_preference: routing_preference(&body["routingPreference"]) as i32,
+        departure_time: timestamp(&body["departureTime"])?,
+        arrival_time: timestamp(&body["arrivalTime"])?,
+        language_code: body["languageCode"].as_str().unwrap_or_default().to_owned(),
+        region_code: body["regionCode"].as_str().unwrap_or_default().to_owned(),
+        units: units(&body["units"]) as i32,
+    })
+}
+
+fn waypoint(value: &Value) -> Result<proto::Waypoint> {
+    check_fields(value, &WAYPOINT_FIELDS, "waypoint")?;
+    let location_type = if let Some(location) = value.get("location") {
+        check_fields(location, &LOCATION_FIELDS, "location")?;
+        proto::waypoint::LocationType::Location(proto::Location {
+            lat_lng: Some(lat_lng(&location["latLng"])),
+            heading: location["heading"].as_i64().map(|heading| heading as i32),
+        })
+    } else if let Some(place_id) = value["placeId"].as_str() {
+        proto::waypoint::LocationType::PlaceId(place_id.to_owned())
+    } else if let Some(address) = value["address"].as_str() {
+        proto::waypoint::LocationType::Address(address.to_owned())
+    } else {
+        return Err(anyhow!("Waypoint requires a location, place ID or address!"));
+    };
+    Ok(proto::Waypoint {
+        location_type: Some(location_type),
+        via: value["via"].as_bool().unwrap_or_default(),
+        vehicle_stopover: value["vehicleStopover"].as_bool().unwrap_or_default(),
+        side_of_road: value["sideOfRoad"].as_bool().unwrap_or_default(),
+    })
+}
+
+/// Fail if the JSON object sets fields other than the given ones, which the gRPC messages cannot carry.
+fn check_fields(value: &Value, fields: &[&str], name: &str) -> Result<()> {
+    let unsupported: Vec<&str> = value
+        .as_object()
+        .into_iter()
+        .flat_map(|object| object.keys())
+        .map(String::as_str)
+        .filter(|field| !fields.contains(field))
+        .collect();
+    if unsupported.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("{} of the {} is not supported over gRPC! Send the request over REST instead.", unsupported.join(", "), name))
+    }
+}
+
+fn lat_lng(value: &Value) -> proto::LatLng {
+    proto::LatLng {
+        latitude: value["latitude"].as_f64().unwrap_or_default(),
+        longitude: value["longitude"].as_f64().unwrap_or_default(),
+    }
+}
+
+fn timestamp(value: &Value) -> Result<Option<prost_types::Timestamp>> {
+    let Some(value) = value.as_str() else {
+        return Ok(None);
+    };
+    let time = DateTime::parse_from_rfc3339(value)?;
+    Ok(Some(prost_types::Timestamp { seconds: time.timestamp(), nanos: time.timestamp_subsec_nanos() as i32 }))
+}
+
+fn travel_mode(value: &Value) -> proto::RouteTravelMode {
+    match value.as_str().unwrap_or_default() {
+        "DRIVE" => proto::RouteTravelMode::Drive,
+        // the REST model spells it BYCYCLE
+        "BICYCLE" | "BYCYCLE" => proto::RouteTravelMode::Bicycle,
+        "WALK" => proto::RouteTravelMode::Walk,
+        "TWO_WHEELER" => proto::RouteTravelMode::TwoWheeler,
+        "TRANSIT" => proto::RouteTravelMode::Transit,
+        _ => proto::RouteTravelMode::TravelModeUnspecified,
+    }
+}
+
+fn routing_preference(value: &Value) -> proto::RoutingPreference {
+    match value.as_str().unwrap_or_default() {
+        "TRAFFIC_UNAWARE" => proto::RoutingPreference::TrafficUnaware,
+        "TRAFFIC_AWARE" => proto::RoutingPreference::TrafficAware,
+        "TRAFFIC_AWARE_OPTIMAL" => proto::RoutingPreference::TrafficAwareOptimal,
+        _ => proto::RoutingPreference::Unspecified,
+    }
+}
+
+fn units(value: &Value) -> proto::Units {
+    match value.as_str().unwrap_or_default() {
+        "METRIC" => proto::Units::Metric,
+        "IMPERIAL" => proto::Units::Imperial,
+        _ => proto::Units::Unspecified,
+    }
+}
+
+
+// Responses are converted to the JSON of the REST API, leaving out unset fields as the REST API does,
+// and then deserialized into the REST response models.
+
+fn route_json(route: &proto::Route) -> Value {
+    let mut json = Map::new();
+    let labels: Vec<Value> = route.route_labels.iter().filter_map(|label| route_label(*label)).map(Value::from).collect();
+    insert_if(&mut json, "routeLabels", !labels.is_empty(), || json!(labels));
+    insert_if(&mut json, "legs", !route.legs.is_empty(), || Value::Array(route.legs.iter().map(route_leg_json).collect()));
+    insert_if(&mut json, "distanceMeters", route.distance_meters != 0, || json!(route.distance_meters));
+    insert_duration(&mut json, "duration", &route.duration);
+    insert_duration(&mut json, "staticDuration", &route.static_duration);
+    insert_if(&mut json, "polyline", route.polyline.is_some(), || polyline_json(route.polyline.as_ref()));
+    insert_if(&mut json, "description", !route.description.is_empty(), || json!(route.description));
+    insert_if(&mut json, "warnings", !route.warnings.is_empty(), || json!(route.warnings));
+    if let Some(viewport) = &route.viewport {
+        json.insert("viewport".to_owned(), json!({
+            "low": viewport.low.as_ref().map(lat_lng_json),
+            "high": viewport.high.as_ref().map(lat_lng_json),
+        }));
+    }
+    insert_if(&mut json, "optimizedIntermediateWaypointIndex", !route.optimized_intermediate_waypoint_index.is_empty(), || json!(route.optimized_intermediate_waypoint_index));
+    insert_if(&mut json, "routeToken", !route.route_token.is_empty(), || json!(route.route_token));
+    Value::Object(json)
+}
+
+fn route_leg_json(leg: &proto::RouteLeg) -> Value {
+    let mut json = Map::new();
+    insert_if(&mut json, "distanceMeters", leg.distance_meters != 0, || json!(leg.distance_meters));
+    insert_duration(&mut json, "duration", &leg.duration);
+    insert_duration(&mut json, "staticDuration", &leg.static_duration);
+    insert_if(&mut json, "polyline", leg.polyline.is_some(), || polyline_json(leg.polyline.as_ref()));
+    if let Some(location) = &leg.start_location {
+        json.insert("startLocation".to_owned(), location_json(location));
+    }
+    if let Some(location) = &leg.end_location {
+        json.insert("endLocation".to_owned(), location_json(location));
+    }
+    Value::Object(json)
+}
+
+fn route_matrix_element_json(element: &proto::RouteMatrixElement) -> Value {
+    let mut json = Map::new();
+    // the REST response models require the indexes
+    json.insert("originIndex".to_owned(), json!(element.origin_index.unwrap_or_default()));
+    json.insert("destinationIndex".to_owned(), json!(element.destination_index.unwrap_or_default()));
+    if let Some(status) = &element.status {
+        let mut status_json = Map::new();
+        insert_if(&mut status_json, "code", status.code != 0, || json!(status.code));
+        insert_if(&mut status_json, "message", !status.message.is_empty(), || json!(status.message));
+        json.insert("status".to_owned(), Value::Object(status_json));
+    }
+    let condition = match proto::RouteMatrixElementCondition::try_from(element.condition) {
+        Ok(proto::RouteMatrixElementCondition::RouteExists) => Some("ROUTE_EXISTS"),
+        Ok(proto::RouteMatrixElementCondition::RouteNotFound) => Some("ROUTE_NOT_FOUND"),
+        _ => None,
+    };
+    if let Some(condition) = condition {
+        json.insert("condition".to_owned(), json!(condition));
+    }
+    insert_if(&mut json, "distanceMeters", element.distance_meters != 0, || json!(element.distance_meters));
+    insert_duration(&mut json, "duration", &element.duration);
+    insert_duration(&mut json, "staticDuration", &element.static_duration);
+    Value::Object(json)
+}
+
+fn route_label(label: i32) -> Option<&'static str> {
+    match proto::RouteLabel::try_from(label).ok()? {
+        proto::RouteLabel::DefaultRoute => Some("DEFAULT_ROUTE"),
+        proto::RouteLabel::DefaultRouteAlternate => Some("DEFAULT_ROUTE_ALTERNATE"),
+        proto::RouteLabel::FuelEfficient => Some("FUEL_EFFICIENT"),
+        proto::RouteLabel::Unspecified => None,
+    }
+}
+
+fn polyline_json(polyline: Option<&proto::Polyline>) -> Value {
+    match polyline.and_then(|polyline| polyline.polyline_type.as_ref()) {
+        Some(proto::polyline::PolylineType::EncodedPolyline(encoded_polyline)) => json!({ "encodedPolyline": encoded_polyline }),
+        None => json!({}),
+    }
+}
+
+fn location_json(location: &proto::Location) -> Value {
+    let mut json = json!({ "latLng": location.lat_lng.as_ref().map(lat_lng_json) });
+    if let Some(heading) = location.heading {
+        json["heading"] = json!(heading);
+    }
+    json
+}
+
+fn lat_lng_json(lat_lng: &proto::LatLng) -> Value {
+    json!({ "latitude": lat_lng.latitude, "longitude": lat_lng.longitude })
+}
+
+/// `google.protobuf.Duration` in JSON, such as `1.5s`.
+fn duration_json(duration: &prost_types::Duration) -> String {
+    if duration.nanos == 0 {
+        format!("{}s", duration.seconds)
+    } else {
+        // seconds and nanos have the same sign, so the sign of a duration under a second is in nanos
+        let sign = if duration.seconds < 0 || duration.nanos < 0 { "-" } else { "" };
+        let nanos = format!("{:09}", duration.nanos.unsigned_abs());
+        format!("{}{}.{}s", sign, duration.seconds.unsigned_abs(), nanos.trim_end_matches('0'))
+    }
+}
+
+fn insert_duration(json: &mut Map<String, Value>, name: &str, duration: &Option<prost_types::Duration>) {
+    if let Some(duration) = duration {
+        json.insert(name.to_owned(), json!(duration_json(duration)));
+    }
+}
+
+fn insert_if(json: &mut Map<String, Value>, name: &str, condition: bool, value: impl FnOnce() -> Value) {
+    if condition {
+        json.insert(name.to_owned(), value());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duration(seconds: i64, nanos: i32) -> prost_types::Duration {
+        prost_types::Duration { seconds, nanos }
+    }
+
+    #[test]
+    fn field_mask_uses_proto_field_names() {
+        assert_eq!(grpc_field_mask("routes.duration,routes.distanceMeters"), "routes.duration,routes.distance_meters");
+        assert_eq!(grpc_field_mask("routes.travelAdvisory.tollInfo"), "routes.travel_advisory.toll_info");
+        assert_eq!(grpc_field_mask("*"), "*");
+    }
+
+    #[test]
+    fn field_mask_paths_must_be_mapped() -> Result<()> {
+        assert_eq!(supported_field_mask("routes.duration, routes.legs.startLocation.latLng", &ROUTE_PATHS)?, "routes.duration,routes.legs.startLocation.latLng");
+        assert_eq!(supported_field_mask("*", &ROUTE_MATRIX_ELEMENT_PATHS)?, ROUTE_MATRIX_ELEMENT_PATHS.join(","));
+        assert!(supported_field_mask("routes.travelAdvisory.tollInfo", &ROUTE_PATHS).is_err());
+        assert!(supported_field_mask("routes.legs", &ROUTE_PATHS).is_err());
+        assert!(supported_field_mask("routes.durationInTraffic", &ROUTE_PATHS).is_err());
+        assert!(supported_field_mask("localizedValues", &ROUTE_MATRIX_ELEMENT_PATHS).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_request_fields_are_rejected() {
+        let origin = json!({ "address": "A" });
+        let error = compute_routes_request(&json!({ "origin": origin, "destination": origin, "trafficModel": "PESSIMISTIC", "routeModifiers": {} })).unwrap_err();
+        assert_eq!(error.to_string(), "routeModifiers, trafficModel of the ComputeRoutes request is not supported over gRPC! Send the request over REST instead.");
+        assert!(compute_routes_request(&json!({ "origin": { "address": "A", "unknown": 1 }, "destination": origin })).is_err());
+
+        let matrix_origin = json!({ "waypoint": origin, "routeModifiers": { "avoidTolls": true } });
+        assert!(compute_route_matrix_request(&json!({ "origins": [matrix_origin], "destinations": [{ "waypoint": origin }] })).is_err());
+        assert!(compute_route_matrix_request(&json!({ "origins": [{ "waypoint": origin }], "destinations": [], "extraComputations": ["TOLLS"] })).is_err());
+    }
+
+    #[test]
+    fn durations_in_json() {
+        assert_eq!(duration_json(&duration(95, 0)), "95s");
+        assert_eq!(duration_json(&duration(95, 500_000_000)), "95.5s");
+        assert_eq!(duration_json(&duration(1, 1)), "1.000000001s");
+        assert_eq!(duration_json(&duration(-1, -250_000_000)), "-1.25s");
+        assert_eq!(duration_json(&duration(0, -500_000_000)), "-0.5s");
+        assert_eq!(duration_json(&duration(0, 0)), "0s");
+    }
+
+    #[test]
+    fn compute_routes_request_from_json() -> Result<()> {
+        let request = compute_routes_request(&json!({
+            "origin": { "location": { "latLng": { "latitude": 37.5, "longitude": -122.25 }, "heading": 90 } },
+            "destination": { "placeId": "ChIJ" },
+            "intermediates": [{ "address": "Mountain View", "via": true }],
+            "travelMode": "BYCYCLE",
+            "routingPreference": "TRAFFIC_AWARE",
+            "departureTime": "2024-01-01T00:00:01.5Z",
+            "computeAlternativeRoutes": true,
+            "languageCode": "en-US",
+            "units": "IMPERIAL",
+        }))?;
+
+        let origin = proto::Location { lat_lng: Some(proto::LatLng { latitude: 37.5, longitude: -122.25 }), heading: Some(90) };
+        assert_eq!(request.origin.unwrap().location_type, Some(proto::waypoint::LocationType::Location(origin)));
+        assert_eq!(request.destination.unwrap().location_type, Some(proto::waypoint::LocationType::PlaceId("ChIJ".to_owned())));
+        assert_eq!(request.intermediates.len(), 1);
+        assert!(request.intermediates[0].via);
+        assert_eq!(request.travel_mode, proto::RouteTravelMode::Bicycle as i32);
+        assert_eq!(request.routing_preference, proto::RoutingPreference::TrafficAware as i32);
+        assert_eq!(request.departure_time, Some(prost_types::Timestamp { seconds: 1_704_067_201, nanos: 500_000_000 }));
+        assert_eq!(request.arrival_time, None);
+        assert!(request.compute_alternative_routes);
+        assert_eq!(request.language_code, "en-US");
+        assert_eq!(request.units, proto::Units::Imperial as i32);
+        Ok(())
+    }
+
+    #[test]
+    fn compute_routes_request_requires_waypoints() {
+        assert!(compute_routes_request(&json!({ "origin": { "placeId": "ChIJ" }, "destination": {} })).is_err());
+    }
+
+    #[test]
+    fn compute_route_matrix_request_from_json() -> Result<()> {
+        let request = compute_route_matrix_request(&json!({
+            "origins": [{ "waypoint": { "address": "A" } }, { "waypoint": { "address": "B" } }],
+            "destinations": [{ "waypoint": { "placeId": "C" } }],
+            "travelMode": "DRIVE",
+        }))?;
+
+        assert_eq!(request.origins.len(), 2);
+        assert_eq!(request.origins[1].waypoint.as_ref().unwrap().location_type, Some(proto::waypoint::LocationType::Address("B".to_owned())));
+        assert_eq!(request.destinations[0].waypoint.as_ref().unwrap().location_type, Some(proto::waypoint::LocationType::PlaceId("C".to_owned())));
+        assert_eq!(request.travel_mode, proto::RouteTravelMode::Drive as i32);
+        assert_eq!(request.routing_preference, proto::RoutingPreference::Unspecified as i32);
+        Ok(())
+    }
+
+    #[test]
+    fn route_to_json() -> Result<()> {
+        let route = proto::Route {
+            route_labels: vec![proto::RouteLabel::DefaultRoute as i32, proto::RouteLabel::Unspecified as i32],
+            legs: vec![proto::RouteLeg {
+                distance_meters: 1200,
+                start_location: Some(proto::Location { lat_lng: Some(proto::LatLng { latitude: 1.5, longitude: 2.5 }), heading: None }),
+                ..Default::default()
+            }],
+            distance_meters: 1200,
+            duration: Some(duration(95, 500_000_000)),
+            polyline: Some(proto::Polyline { polyline_type: Some(proto::polyline::PolylineType::EncodedPolyline("_p~iF".to_owned())) }),
+            optimized_intermediate_waypoint_index: vec![1, 0],
+            ..Default::default()
+        };
+
+        let json = route_json(&route);
+        assert_eq!(json, json!({
+            "routeLabels": ["DEFAULT_ROUTE"],
+            "legs": [{ "distanceMeters": 1200, "startLocation": { "latLng": { "latitude": 1.5, "longitude": 2.5 } } }],
+            "distanceMeters": 1200,
+            "duration": "95.5s",
+            "polyline": { "encodedPolyline": "_p~iF" },
+            "optimizedIntermediateWaypointIndex": [1, 0],
+        }));
+        let response: ComputeRouteResponse = serde_json::from_value(json!({ "routes": [json] }))?;
+        assert_eq!(response.routes[0].distance_meters, Some(1200));
+        Ok(())
+    }
+
+    #[test]
+    fn route_matrix_element_to_json() -> Result<()> {
+        let element = proto::RouteMatrixElement {
+            origin_index: Some(1),
+            destination_index: None,
+            status: Some(proto::Status { code: 5, message: "not found".to_owned() }),
+            condition: proto::RouteMatrixElementCondition::RouteNotFound as i32,
+            ..Default::default()
+        };
+
+        let json = route_matrix_element_json(&element);
+        assert_eq!(json, json!({
+            "originIndex": 1,
+            "destinationIndex": 0,
+            "status": { "code": 5, "message": "not found" },
+            "condition": "ROUTE_NOT_FOUND",
+        }));
+        let element: ComputeRouteMatrixResponse = serde_json::from_value(json)?;
+        assert_eq!((element.origin_index, element.distance_meters), (1, None));
+        Ok(())
+    }
+}
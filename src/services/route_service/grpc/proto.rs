@@ -0,0 +1,247 @@
+//! Hand-written subset of the `google.maps.routing.v2` messages used by `RouteService`, in the form `prost-build` generates.
+//! Only the fields mapped to the crate's request and response models are included, with the field numbers and enum values
+//! of the protos; unknown fields are skipped when decoding. Add fields with their proto tags as the models grow, together with
+//! the supported request fields and response paths in `grpc`, which reject requests these messages cannot carry.
+//! See https://github.com/googleapis/googleapis/tree/master/google/maps/routing/v2
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LatLng {
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Location {
+    #[prost(message, optional, tag = "1")]
+    pub lat_lng: ::core::option::Option<LatLng>,
+    #[prost(message, optional, tag = "2")]
+    pub heading: ::core::option::Option<i32>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Waypoint {
+    #[prost(bool, tag = "3")]
+    pub via: bool,
+    #[prost(bool, tag = "4")]
+    pub vehicle_stopover: bool,
+    #[prost(bool, tag = "5")]
+    pub side_of_road: bool,
+    #[prost(oneof = "waypoint::LocationType", tags = "1, 2, 7")]
+    pub location_type: ::core::option::Option<waypoint::LocationType>,
+}
+
+pub mod waypoint {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum LocationType {
+        #[prost(message, tag = "1")]
+        Location(super::Location),
+        #[prost(string, tag = "2")]
+        PlaceId(::prost::alloc::string::String),
+        #[prost(string, tag = "7")]
+        Address(::prost::alloc::string::String),
+    }
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ComputeRoutesRequest {
+    #[prost(message, optional, tag = "1")]
+    pub origin: ::core::option::Option<Waypoint>,
+    #[prost(message, optional, tag = "2")]
+    pub destination: ::core::option::Option<Waypoint>,
+    #[prost(message, repeated, tag = "3")]
+    pub intermediates: ::prost::alloc::vec::Vec<Waypoint>,
+    #[prost(enumeration = "RouteTravelMode", tag = "4")]
+    pub travel_mode: i32,
+    #[prost(enumeration = "RoutingPreference", tag = "5")]
+    pub routing_preference: i32,
+    #[prost(message, optional, tag = "7")]
+    pub departure_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(bool, tag = "8")]
+    pub compute_alternative_routes: bool,
+    #[prost(string, tag = "10")]
+    pub language_code: ::prost::alloc::string::String,
+    #[prost(enumeration = "Units", tag = "11")]
+    pub units: i32,
+    #[prost(string, tag = "16")]
+    pub region_code: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "19")]
+    pub arrival_time: ::core::option::Option<::prost_types::Timestamp>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ComputeRoutesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub routes: ::prost::alloc::vec::Vec<Route>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Route {
+    #[prost(enumeration = "RouteLabel", repeated, tag = "13")]
+    pub route_labels: ::prost::alloc::vec::Vec<i32>,
+    #[prost(message, repeated, tag = "1")]
+    pub legs: ::prost::alloc::vec::Vec<RouteLeg>,
+    #[prost(int32, tag = "2")]
+    pub distance_meters: i32,
+    #[prost(message, optional, tag = "3")]
+    pub duration: ::core::option::Option<::prost_types::Duration>,
+    #[prost(message, optional, tag = "4")]
+    pub static_duration: ::core::option::Option<::prost_types::Duration>,
+    #[prost(message, optional, tag = "5")]
+    pub polyline: ::core::option::Option<Polyline>,
+    #[prost(string, tag = "6")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "7")]
+    pub warnings: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "8")]
+    pub viewport: ::core::option::Option<Viewport>,
+    #[prost(int32, repeated, tag = "10")]
+    pub optimized_intermediate_waypoint_index: ::prost::alloc::vec::Vec<i32>,
+    #[prost(string, tag = "12")]
+    pub route_token: ::prost::alloc::string::String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RouteLeg {
+    #[prost(int32, tag = "1")]
+    pub distance_meters: i32,
+    #[prost(message, optional, tag = "2")]
+    pub duration: ::core::option::Option<::prost_types::Duration>,
+    #[prost(message, optional, tag = "3")]
+    pub static_duration: ::core::option::Option<::prost_types::Duration>,
+    #[prost(message, optional, tag = "4")]
+    pub polyline: ::core::option::Option<Polyline>,
+    #[prost(message, optional, tag = "5")]
+    pub start_location: ::core::option::Option<Location>,
+    #[prost(message, optional, tag = "6")]
+    pub end_location: ::core::option::Option<Location>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Polyline {
+    #[prost(oneof = "polyline::PolylineType", tags = "1")]
+    pub polyline_type: ::core::option::Option<polyline::PolylineType>,
+}
+
+pub mod polyline {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum PolylineType {
+        #[prost(string, tag = "1")]
+        EncodedPolyline(::prost::alloc::string::String),
+    }
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Viewport {
+    #[prost(message, optional, tag = "1")]
+    pub low: ::core::option::Option<LatLng>,
+    #[prost(message, optional, tag = "2")]
+    pub high: ::core::option::Option<LatLng>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ComputeRouteMatrixRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub origins: ::prost::alloc::vec::Vec<RouteMatrixOrigin>,
+    #[prost(message, repeated, tag = "2")]
+    pub destinations: ::prost::alloc::vec::Vec<RouteMatrixDestination>,
+    #[prost(enumeration = "RouteTravelMode", tag = "3")]
+    pub travel_mode: i32,
+    #[prost(enumeration = "RoutingPreference", tag = "4")]
+    pub routing_preference: i32,
+    #[prost(message, optional, tag = "5")]
+    pub departure_time: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(string, tag = "6")]
+    pub language_code: ::prost::alloc::string::String,
+    #[prost(enumeration = "Units", tag = "7")]
+    pub units: i32,
+    #[prost(string, tag = "9")]
+    pub region_code: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "11")]
+    pub arrival_time: ::core::option::Option<::prost_types::Timestamp>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RouteMatrixOrigin {
+    #[prost(message, optional, tag = "1")]
+    pub waypoint: ::core::option::Option<Waypoint>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RouteMatrixDestination {
+    #[prost(message, optional, tag = "1")]
+    pub waypoint: ::core::option::Option<Waypoint>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RouteMatrixElement {
+    #[prost(int32, optional, tag = "1")]
+    pub origin_index: ::core::option::Option<i32>,
+    #[prost(int32, optional, tag = "2")]
+    pub destination_index: ::core::option::Option<i32>,
+    #[prost(message, optional, tag = "3")]
+    pub status: ::core::option::Option<Status>,
+    #[prost(int32, tag = "4")]
+    pub distance_meters: i32,
+    #[prost(message, optional, tag = "5")]
+    pub duration: ::core::option::Option<::prost_types::Duration>,
+    #[prost(message, optional, tag = "6")]
+    pub static_duration: ::core::option::Option<::prost_types::Duration>,
+    #[prost(enumeration = "RouteMatrixElementCondition", tag = "9")]
+    pub condition: i32,
+}
+
+/// `google.rpc.Status`, without the details.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Status {
+    #[prost(int32, tag = "1")]
+    pub code: i32,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RouteTravelMode {
+    TravelModeUnspecified = 0,
+    Drive = 1,
+    Bicycle = 2,
+    Walk = 3,
+    TwoWheeler = 4,
+    Transit = 7,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RoutingPreference {
+    Unspecified = 0,
+    TrafficUnaware = 1,
+    TrafficAware = 2,
+    TrafficAwareOptimal = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Units {
+    Unspecified = 0,
+    Metric = 1,
+    Imperial = 2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RouteLabel {
+    Unspecified = 0,
+    DefaultRoute = 1,
+    DefaultRouteAlternate = 2,
+    FuelEfficient = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RouteMatrixElementCondition {
+    Unspecified = 0,
+    RouteExists = 1,
+    RouteNotFound = 2,
+}
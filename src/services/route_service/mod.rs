@@ -1,19 +1,40 @@
 
 
+use std::sync::Arc;
+
+use anyhow::Result;
+use reqwest::{Client, Url};
+
 use crate::auth::service_account::ServiceAccountCredentials;
+use crate::auth::watched_credentials::WatchedServiceAccountCredentials;
+use crate::auth::delegated_credentials_pool::DelegatedCredentials;
 use super::ServiceBase;
+use super::client_config::ClientConfig;
+use super::retry_policy::RetryPolicy;
+use super::rate_limiter::RateLimiter;
+use super::response_cache::ResponseCache;
+use super::http_service::{BoxError, HttpRequest, HttpResponse, HttpService};
+use super::http_transport::HttpTransport;
 
 pub mod get_route;
 pub mod get_route_matrix;
 pub mod common_models;
+pub mod routes_api;
+pub mod field_mask;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 
 static ROUTE_SERVICE_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
-static GET_ROUTE_URL: &str = "https://routes.googleapis.com/directions/v2:computeRoutes";
-static GET_ROUTE_MATRIX_URL: &str = "https://routes.googleapis.com/distanceMatrix/v2:computeRouteMatrix";
+static ROUTE_SERVICE_ENDPOINT: &str = "https://routes.googleapis.com";
+static ROUTE_SERVICE_ENDPOINT_ENV: &str = "GOOGLE_ROUTES_ENDPOINT";
+static GET_ROUTE_PATH: &str = "directions/v2:computeRoutes";
+static GET_ROUTE_MATRIX_PATH: &str = "distanceMatrix/v2:computeRouteMatrix";
 
 #[derive(Debug, Clone)]
 pub struct RouteService {
-    base: ServiceBase
+    base: ServiceBase,
+    #[cfg(feature = "grpc")]
+    grpc: Option<grpc::GrpcChannel>,
 }
 
 
@@ -22,13 +43,150 @@ impl RouteService {
     ///
     /// * `api_key` -  API key to use to authenticate to Google Cloud APIs and services that support API keys.
     pub fn new_with_api_key(api_key: String) -> Self {
-        return Self { base: ServiceBase::new_with_api_key(api_key) }
+        Self::from_base(ServiceBase::new_with_api_key(api_key))
     }
 
     /// Create `RouteService` Authenticate by using API keys.
     ///
     /// * `service_account_credentials` -  `ServiceAccountCredentials` to use to authenticate to Google Cloud APIs.
     pub fn new_with_credentials(service_account_credentials: ServiceAccountCredentials) -> Self {
-        return Self { base: ServiceBase::new_with_credentials(service_account_credentials, vec![ROUTE_SERVICE_SCOPE]) }
+        Self::from_base(ServiceBase::new_with_credentials(service_account_credentials, vec![ROUTE_SERVICE_SCOPE]))
+    }
+
+    /// Create `RouteService` Authenticate by using a watched service account key file.
+    /// The service switches to the new key when the file is rotated on disk.
+    ///
+    /// * `watched_credentials` -  `WatchedServiceAccountCredentials` to use to authenticate to Google Cloud APIs.
+    pub fn new_with_watched_credentials(watched_credentials: WatchedServiceAccountCredentials) -> Self {
+        Self::from_base(ServiceBase::new_with_watched_credentials(watched_credentials, vec![ROUTE_SERVICE_SCOPE]))
+    }
+
+    /// Create `RouteService` Authenticate by using delegated credentials from a `DelegatedCredentialsPool`.
+    /// Tokens are cached in the pool per subject, so creating a service per user does not require a new token exchange.
+    ///
+    /// * `delegated_credentials` -  `DelegatedCredentials` to use to authenticate to Google Cloud APIs.
+    pub fn new_with_delegated_credentials(delegated_credentials: DelegatedCredentials) -> Self {
+        Self::from_base(ServiceBase::new_with_delegated_credentials(delegated_credentials, vec![ROUTE_SERVICE_SCOPE]))
+    }
+
+    /// Use the given HTTP client for all requests of the service, including token requests.
+    /// Clients are cheap to clone and share their connection pool.
+    ///
+    /// * `client` -  `reqwest::Client` to use.
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.base = self.base.with_http_client(client);
+        self.reset_grpc_channel();
+        self
+    }
+
+    /// Use an HTTP client built from the given config for all requests of the service, including token requests.
+    ///
+    /// * `config` -  `ClientConfig` to build the client from.
+    pub fn with_client_config(mut self, config: &ClientConfig) -> Result<Self> {
+        self.base = self.base.with_client_config(config)?;
+        self.reset_grpc_channel();
+        Ok(self)
+    }
+
+    /// Retry transient failures (429, 503, 504 and connection errors) according to the policy.
+    /// By default requests are sent once.
+    ///
+    /// * `retry_policy` -  `RetryPolicy` to use.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.base = self.base.with_retry_policy(retry_policy);
+        self
+    }
+
+    /// Wait for the rate limiter before sending each request, including retries.
+    /// Share the limiter between services that use the same project quota.
+    ///
+    /// * `rate_limiter` -  `RateLimiter` to use.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.base = self.base.with_rate_limiter(rate_limiter);
+        self
+    }
+
+    /// Return cached responses for identical requests instead of sending them again.
+    /// Share the cache between clones of the service to share their responses.
+    ///
+    /// * `response_cache` -  `ResponseCache` to use.
+    pub fn with_response_cache(mut self, response_cache: ResponseCache) -> Self {
+        self.base = self.base.with_response_cache(response_cache);
+        self
+    }
+
+    /// Add a `tower` layer, such as a timeout, concurrency limit or logging layer, to the HTTP stack of the service.
+    /// Layers added last see requests first. Clones of the service share one instance of the stack,
+    /// so stateful layers such as `RateLimitLayer` and `ConcurrencyLimitLayer` limit all their requests.
+    ///
+    /// * `layer` -  `tower::Layer` wrapping an `HttpService`.
+    pub fn with_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<HttpService> + Send + Sync + 'static,
+        L::Service: tower::Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Send + 'static,
+        <L::Service as tower::Service<HttpRequest>>::Future: Send + 'static,
+    {
+        self.base = self.base.with_layer(layer);
+        self
+    }
+
+    /// Send requests with the given transport instead of `reqwest`, for example hyper directly or an in-memory transport for tests.
+    /// Token requests of service account credentials are still sent with the `reqwest::Client`.
+    ///
+    /// * `transport` -  `HttpTransport` to send requests with.
+    pub fn with_transport<T: HttpTransport + 'static>(mut self, transport: T) -> Self {
+        self.base = self.base.with_transport(Arc::new(transport));
+        self
+    }
+
+    /// Send requests to the given endpoint instead of `https://routes.googleapis.com`,
+    /// for example a Private Service Connect address, a proxy or a local test server.
+    /// The endpoint can also be set with the `GOOGLE_ROUTES_ENDPOINT` environment variable.
+    ///
+    /// * `endpoint` -  Base URL of the API. Any path is kept as a prefix of the API paths.
+    pub fn with_endpoint(mut self, endpoint: Url) -> Self {
+        self.base = self.base.with_endpoint(endpoint);
+        self.reset_grpc_channel();
+        self
+    }
+
+    /// Send `get_route` and `get_route_matrix` requests over gRPC instead of REST, to the same endpoint.
+    /// Route matrix elements can then also be streamed as they are computed with `get_route_matrix_stream`.
+    ///
+    /// The retry policy and rate limiter apply to gRPC calls, retrying `RESOURCE_EXHAUSTED`, `UNAVAILABLE` and
+    /// `DEADLINE_EXCEEDED`. The response cache, `tower` layers, `HttpTransport` and cassettes apply to REST requests only,
+    /// so gRPC calls are never cached, recorded or replayed.
+    ///
+    /// The gRPC messages only carry the waypoints, `intermediates`, `travelMode`, `routingPreference`, `departureTime`,
+    /// `arrivalTime`, `computeAlternativeRoutes`, `languageCode`, `regionCode` and `units`; calls with other parameters fail.
+    /// Field masks may only name the route paths `routeLabels`, `legs.distanceMeters`, `legs.duration`, `legs.staticDuration`,
+    /// `legs.polyline`, `legs.startLocation`, `legs.endLocation`, `distanceMeters`, `duration`, `staticDuration`, `polyline`,
+    /// `description`, `warnings`, `viewport`, `optimizedIntermediateWaypointIndex` and `routeToken`, and the matrix element paths
+    /// `originIndex`, `destinationIndex`, `status`, `condition`, `distanceMeters`, `duration` and `staticDuration`;
+    /// `*` selects all of them.
+    #[cfg(feature = "grpc")]
+    pub fn with_grpc(mut self) -> Self {
+        self.grpc = Some(grpc::GrpcChannel::default());
+        self
+    }
+
+    // the gRPC channel is connected to the endpoint and with the client certificate of the service when first used
+    fn reset_grpc_channel(&mut self) {
+        #[cfg(feature = "grpc")]
+        if self.grpc.is_some() {
+            self.grpc = Some(grpc::GrpcChannel::default());
+        }
+    }
+
+    fn from_base(base: ServiceBase) -> Self {
+        Self {
+            base,
+            #[cfg(feature = "grpc")]
+            grpc: None,
+        }
+    }
+
+    fn url(&self, path: &str) -> Result<Url> {
+        self.base.endpoint_url(ROUTE_SERVICE_ENDPOINT, ROUTE_SERVICE_ENDPOINT_ENV, path)
     }
 }
\ No newline at end of file
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::RouteService;
+use crate::services::request_options::RequestOptions;
+use super::common_models::WayPoint;
+use super::get_route::response_model::ComputeRouteResponse;
+use super::get_route_matrix::request_model::RouteMatrixOrigin;
+use super::get_route_matrix::response_model::ComputeRouteMatrixResponse;
+
+
+/// The Routes API methods of `RouteService`.
+///
+/// Depend on this trait instead of `RouteService` to substitute the service in tests.
+/// With the `mockall` feature, `MockRoutesApi` implements it.
+#[cfg_attr(feature = "mockall", mockall::automock)]
+#[async_trait]
+pub trait RoutesApi: Send {
+    /// Calculate the distance and duration of a route. See `RouteService::get_route`.
+    async fn get_route<'a>(&mut self, origin: &WayPoint, destination: &WayPoint, response_masks: Option<Vec<&'a str>>, params: Option<HashMap<String, Value>>) -> Result<ComputeRouteResponse>;
+
+    /// Calculate the distance and duration of routes for multiple origins and destinations. See `RouteService::get_route_matrix`.
+    async fn get_route_matrix<'a>(&mut self, origin: &[RouteMatrixOrigin], destination: &[RouteMatrixOrigin], response_masks: Option<Vec<&'a str>>, params: Option<HashMap<String, Value>>) -> Result<Vec<ComputeRouteMatrixResponse>>;
+
+    /// `get_route` with per-request options. See `RouteService::get_route_with_options`.
+    async fn get_route_with_options<'a>(&mut self, origin: &WayPoint, destination: &WayPoint, response_masks: Option<Vec<&'a str>>, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<ComputeRouteResponse>;
+
+    /// `get_route_matrix` with per-request options. See `RouteService::get_route_matrix_with_options`.
+    async fn get_route_matrix_with_options<'a>(&mut self, origin: &[RouteMatrixOrigin], destination: &[RouteMatrixOrigin], response_masks: Option<Vec<&'a str>>, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<Vec<ComputeRouteMatrixResponse>>;
+}
+
+#[async_trait]
+impl RoutesApi for RouteService {
+    async fn get_route<'a>(&mut self, origin: &WayPoint, destination: &WayPoint, response_masks: Option<Vec<&'a str>>, params: Option<HashMap<String, Value>>) -> Result<ComputeRouteResponse> {
+        RouteService::get_route(self, origin, destination, response_masks, params).await
+    }
+
+    async fn get_route_matrix<'a>(&mut self, origin: &[RouteMatrixOrigin], destination: &[RouteMatrixOrigin], response_masks: Option<Vec<&'a str>>, params: Option<HashMap<String, Value>>) -> Result<Vec<ComputeRouteMatrixResponse>> {
+        RouteService::get_route_matrix(self, &origin.to_vec(), &destination.to_vec(), response_masks, params).await
+    }
+
+    async fn get_route_with_options<'a>(&mut self, origin: &WayPoint, destination: &WayPoint, response_masks: Option<Vec<&'a str>>, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<ComputeRouteResponse> {
+        RouteService::get_route_with_options(self, origin, destination, response_masks, params, options).await
+    }
+
+    async fn get_route_matrix_with_options<'a>(&mut self, origin: &[RouteMatrixOrigin], destination: &[RouteMatrixOrigin], response_masks: Option<Vec<&'a str>>, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<Vec<ComputeRouteMatrixResponse>> {
+        RouteService::get_route_matrix_with_options(self, &origin.to_vec(), &destination.to_vec(), response_masks, params, options).await
+    }
+}
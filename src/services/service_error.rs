@@ -1,20 +1,18 @@
-use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{de::DeserializeOwned, Serialize, Deserialize};
 use serde_json::Value;
 
 
 // https://cloud.google.com/apis/design/errors
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Default)]
 pub struct ServiceErrorResponse {
     pub error: ErrorResponseStatus
 }
 
-impl Default for ServiceErrorResponse {
-    fn default() -> Self {
-        Self {
-            error: ErrorResponseStatus::default()
-        }
-    }
-}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponseStatus {
@@ -39,3 +37,371 @@ impl Default for ErrorResponseStatus {
         }
     }
 }
+
+
+static ERROR_INFO_TYPE: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+static QUOTA_FAILURE_TYPE: &str = "type.googleapis.com/google.rpc.QuotaFailure";
+static BAD_REQUEST_TYPE: &str = "type.googleapis.com/google.rpc.BadRequest";
+static RETRY_INFO_TYPE: &str = "type.googleapis.com/google.rpc.RetryInfo";
+static HELP_TYPE: &str = "type.googleapis.com/google.rpc.Help";
+static LOCALIZED_MESSAGE_TYPE: &str = "type.googleapis.com/google.rpc.LocalizedMessage";
+
+// canonical codes of https://github.com/googleapis/googleapis/blob/master/google/rpc/code.proto, indexed by code,
+// with their names and HTTP status codes
+static RPC_CODES: [(&str, u16); 17] = [
+    ("OK", 200),
+    ("CANCELLED", 499),
+    ("UNKNOWN", 500),
+    ("INVALID_ARGUMENT", 400),
+    ("DEADLINE_EXCEEDED", 504),
+    ("NOT_FOUND", 404),
+    ("ALREADY_EXISTS", 409),
+    ("PERMISSION_DENIED", 403),
+    ("RESOURCE_EXHAUSTED", 429),
+    ("FAILED_PRECONDITION", 400),
+    ("ABORTED", 409),
+    ("OUT_OF_RANGE", 400),
+    ("UNIMPLEMENTED", 501),
+    ("INTERNAL", 500),
+    ("UNAVAILABLE", 503),
+    ("DATA_LOSS", 500),
+    ("UNAUTHENTICATED", 401),
+];
+
+static SERVICE_DISABLED_REASON: &str = "SERVICE_DISABLED";
+static QUOTA_EXCEEDED_REASONS: [&str; 2] = ["RATE_LIMIT_EXCEEDED", "RESOURCE_QUOTA_EXCEEDED"];
+static RESOURCE_EXHAUSTED_STATUS: &str = "RESOURCE_EXHAUSTED";
+static INVALID_ARGUMENT_STATUS: &str = "INVALID_ARGUMENT";
+
+
+/// Error returned by the services when the API responds with an error status.
+///
+/// Returned inside `anyhow::Error`, use `error.downcast_ref::<ServiceError>()` to inspect it.
+#[derive(Debug, Clone)]
+pub enum ServiceError {
+    /// The response body is a `google.rpc.Status`. See https://cloud.google.com/apis/design/errors
+    Status {
+        /// HTTP status code of the response.
+        http_status: u16,
+        /// Code, canonical status name, message and decoded details of the error.
+        status: StatusError,
+    },
+    /// The response body could not be decoded, such as an HTML page returned by a proxy.
+    Http {
+        /// HTTP status code of the response.
+        http_status: u16,
+        /// Body of the response.
+        body: String,
+    },
+}
+
+/// A decoded `google.rpc.Status`.
+#[derive(Debug, Clone)]
+pub struct StatusError {
+    /// HTTP status code of the error, as in the `code` of REST error responses.
+    pub code: u32,
+    /// Canonical gRPC code of the error, such as 14 for `UNAVAILABLE`.
+    /// For REST responses it is derived from the status name, if any.
+    pub rpc_code: Option<i32>,
+    pub message: String,
+    /// Canonical status name, such as `INVALID_ARGUMENT` or `RESOURCE_EXHAUSTED`.
+    pub status: Option<String>,
+    pub details: Vec<ErrorDetail>,
+}
+
+impl ServiceError {
+    /// Create `ServiceError` from an error response.
+    ///
+    /// * `http_status` -  HTTP status code of the response.
+    /// * `body` -  Body of the response.
+    pub fn from_response(http_status: u16, body: &str) -> Self {
+        match serde_json::from_str::<ServiceErrorResponse>(body) {
+            Ok(response) => Self::Status { http_status, status: response.error.into() },
+            Err(_) => Self::Http { http_status, body: body.to_owned() },
+        }
+    }
+
+    /// Create `ServiceError` from a `google.rpc.Status` with a canonical gRPC code,
+    /// as returned by gRPC calls and failed long-running operations.
+    ///
+    /// * `code` -  Canonical code, such as 3 for `INVALID_ARGUMENT`.
+    /// * `message` -  Message of the error.
+    /// * `details` -  Details of the error, each with its `@type`.
+    pub fn from_rpc_status(code: i32, message: &str, details: Vec<Value>) -> Self {
+        let (name, http_status) = usize::try_from(code).ok().and_then(|code| RPC_CODES.get(code)).copied().unwrap_or(RPC_CODES[2]);
+        Self::Status {
+            http_status,
+            status: StatusError {
+                code: u32::from(http_status),
+                rpc_code: Some(code),
+                message: message.to_owned(),
+                status: Some(name.to_owned()),
+                details: details.into_iter().map(ErrorDetail::from).collect(),
+            },
+        }
+    }
+
+    /// HTTP status code of the response.
+    pub fn http_status(&self) -> u16 {
+        match self {
+            Self::Status { http_status, .. } | Self::Http { http_status, .. } => *http_status,
+        }
+    }
+
+    /// Canonical gRPC code, such as 14 for `UNAVAILABLE`, which tells apart errors sharing an HTTP status.
+    pub fn rpc_code(&self) -> Option<i32> {
+        match self {
+            Self::Status { status, .. } => status.rpc_code,
+            Self::Http { .. } => None,
+        }
+    }
+
+    /// Canonical status name, such as `INVALID_ARGUMENT` or `RESOURCE_EXHAUSTED`.
+    pub fn status(&self) -> Option<&str> {
+        match self {
+            Self::Status { status, .. } => status.status.as_deref(),
+            Self::Http { .. } => None,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Status { status, .. } => &status.message,
+            Self::Http { body, .. } => body,
+        }
+    }
+
+    pub fn details(&self) -> &[ErrorDetail] {
+        match self {
+            Self::Status { status, .. } => &status.details,
+            Self::Http { .. } => &[],
+        }
+    }
+
+    pub fn error_info(&self) -> Option<&ErrorInfo> {
+        self.details().iter().find_map(|detail| match detail {
+            ErrorDetail::ErrorInfo(error_info) => Some(error_info),
+            _ => None,
+        })
+    }
+
+    pub fn quota_failure(&self) -> Option<&QuotaFailure> {
+        self.details().iter().find_map(|detail| match detail {
+            ErrorDetail::QuotaFailure(quota_failure) => Some(quota_failure),
+            _ => None,
+        })
+    }
+
+    pub fn bad_request(&self) -> Option<&BadRequest> {
+        self.details().iter().find_map(|detail| match detail {
+            ErrorDetail::BadRequest(bad_request) => Some(bad_request),
+            _ => None,
+        })
+    }
+
+    pub fn retry_info(&self) -> Option<&RetryInfo> {
+        self.details().iter().find_map(|detail| match detail {
+            ErrorDetail::RetryInfo(retry_info) => Some(retry_info),
+            _ => None,
+        })
+    }
+
+    pub fn help(&self) -> Option<&Help> {
+        self.details().iter().find_map(|detail| match detail {
+            ErrorDetail::Help(help) => Some(help),
+            _ => None,
+        })
+    }
+
+    pub fn localized_message(&self) -> Option<&LocalizedMessage> {
+        self.details().iter().find_map(|detail| match detail {
+            ErrorDetail::LocalizedMessage(localized_message) => Some(localized_message),
+            _ => None,
+        })
+    }
+
+    /// `ErrorInfo` reason, such as `SERVICE_DISABLED` or `API_KEY_INVALID`.
+    pub fn reason(&self) -> Option<&str> {
+        self.error_info().map(|error_info| error_info.reason.as_str())
+    }
+
+    /// Whether the API is not enabled for the project.
+    pub fn is_api_not_enabled(&self) -> bool {
+        self.reason() == Some(SERVICE_DISABLED_REASON)
+    }
+
+    /// Whether a rate limit or quota was exceeded.
+    pub fn is_quota_exceeded(&self) -> bool {
+        self.status() == Some(RESOURCE_EXHAUSTED_STATUS)
+            || self.quota_failure().is_some()
+            || self.reason().is_some_and(|reason| QUOTA_EXCEEDED_REASONS.contains(&reason))
+    }
+
+    /// Whether the request had an invalid argument, such as an invalid waypoint. See `bad_request` for the fields at fault.
+    pub fn is_invalid_argument(&self) -> bool {
+        self.status() == Some(INVALID_ARGUMENT_STATUS) || self.bad_request().is_some()
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { status, .. } => write!(f, "Response Error! Code: {}, Message: {}", status.code, status.message),
+            Self::Http { http_status, .. } => write!(f, "Response Error! Code: {}, Message: Unknown Error", http_status),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<ErrorResponseStatus> for StatusError {
+    fn from(status: ErrorResponseStatus) -> Self {
+        let rpc_code = status.status.as_deref().and_then(|name| RPC_CODES.iter().position(|(code_name, _)| *code_name == name));
+        Self {
+            code: status.code,
+            rpc_code: rpc_code.map(|code| code as i32),
+            message: status.message,
+            status: status.status,
+            details: status.details.unwrap_or_default().into_iter().map(ErrorDetail::from).collect(),
+        }
+    }
+}
+
+
+/// A decoded entry of `google.rpc.Status.details`. See https://cloud.google.com/apis/design/errors#error_details
+#[derive(Debug, Clone)]
+pub enum ErrorDetail {
+    ErrorInfo(ErrorInfo),
+    QuotaFailure(QuotaFailure),
+    BadRequest(BadRequest),
+    RetryInfo(RetryInfo),
+    Help(Help),
+    LocalizedMessage(LocalizedMessage),
+    /// A detail of another type, or one that could not be decoded.
+    Other(Value),
+}
+
+impl From<Value> for ErrorDetail {
+    fn from(value: Value) -> Self {
+        fn decode<T: DeserializeOwned>(value: &Value, detail: fn(T) -> ErrorDetail) -> Option<ErrorDetail> {
+            serde_json::from_value(value.clone()).ok().map(detail)
+        }
+
+        let type_url = value["@type"].as_str().unwrap_or_default();
+        let decoded = if type_url == ERROR_INFO_TYPE {
+            decode(&value, ErrorDetail::ErrorInfo)
+        } else if type_url == QUOTA_FAILURE_TYPE {
+            decode(&value, ErrorDetail::QuotaFailure)
+        } else if type_url == BAD_REQUEST_TYPE {
+            decode(&value, ErrorDetail::BadRequest)
+        } else if type_url == RETRY_INFO_TYPE {
+            decode(&value, ErrorDetail::RetryInfo)
+        } else if type_url == HELP_TYPE {
+            decode(&value, ErrorDetail::Help)
+        } else if type_url == LOCALIZED_MESSAGE_TYPE {
+            decode(&value, ErrorDetail::LocalizedMessage)
+        } else {
+            None
+        };
+        decoded.unwrap_or(ErrorDetail::Other(value))
+    }
+}
+
+/// The reason of the error, such as `SERVICE_DISABLED`, and the domain it comes from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorInfo {
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub domain: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// The quotas that were exceeded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaFailure {
+    #[serde(default)]
+    pub violations: Vec<QuotaViolation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaViolation {
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_service: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota_metric: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota_id: Option<String>,
+}
+
+/// The fields of the request that were invalid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadRequest {
+    #[serde(default)]
+    pub field_violations: Vec<FieldViolation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldViolation {
+    /// Path to the field, such as `origin.location.latLng`.
+    #[serde(default)]
+    pub field: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// How long to wait before retrying.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryInfo {
+    /// `google.protobuf.Duration` in JSON, such as `1.5s`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_delay: Option<String>,
+}
+
+impl RetryInfo {
+    /// The retry delay as a `Duration`.
+    pub fn delay(&self) -> Option<Duration> {
+        let seconds = self.retry_delay.as_ref()?.strip_suffix('s')?.parse::<f64>().ok()?;
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+/// Links to documentation, such as the page to enable an API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Help {
+    #[serde(default)]
+    pub links: Vec<HelpLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelpLink {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// Error message localized for the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedMessage {
+    #[serde(default)]
+    pub locale: String,
+    #[serde(default)]
+    pub message: String,
+}
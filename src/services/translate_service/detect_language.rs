@@ -1,9 +1,13 @@
 
-use super::{TranslateServiceV2Type, TranslateService, TRANSLATE_SERVICE_BASE_URL};
+use super::{TranslateServiceV2Type, TranslateService};
+use crate::services::ApiMethod;
+use crate::services::request_options::RequestOptions;
 
 use serde::{Serialize, Deserialize};
 use anyhow::Result;
-use reqwest::{Client, Url};
+use reqwest::{header::HeaderMap, Method};
+
+static DETECT_LANGUAGE: ApiMethod = ApiMethod { service: "translate", name: "detect_language", idempotent: true };
 
 impl TranslateService {
 
@@ -13,18 +17,28 @@ impl TranslateService {
     ///
     /// * `text` -  an array of strings to upon which to perform language detection.
     pub async fn detect_language(&mut self, text: Vec<&str>) -> Result<DetectLanguageResponse>{
+        self.detect_language_with_options(text, &RequestOptions::default()).await
+    }
+
+    /// Detects the language of texts, with per-request options such as a timeout. <br>
+    /// Arguments are the same as `detect_language`.
+    ///
+    /// * `options` -  `RequestOptions` of the call.
+    #[tracing::instrument(skip_all, fields(method = "detect_language", endpoint, status, latency_ms, retry_count, response_size))]
+    pub async fn detect_language_with_options(&mut self, text: Vec<&str>, options: &RequestOptions) -> Result<DetectLanguageResponse>{
+        options.run(async {
 
-        let base_url = Url::parse(&format!("{}/v2/{}", TRANSLATE_SERVICE_BASE_URL, TranslateServiceV2Type::Detect.path()))?;
-        let headers = self.base.create_headers().await?;
-        let request_body = DetectLanguageRequest::new(text);
-        let builder = Client::new().post(base_url)
-                .headers(headers)
-                .body(serde_json::to_string(&request_body)?);
+            let base_url = self.url(TranslateServiceV2Type::Detect)?;
+            let mut headers = HeaderMap::new();
+            options.apply_headers(&mut headers)?;
+            let request_body = DetectLanguageRequest::new(text);
+            let request = self.base.build_request(Method::POST, base_url, headers, serde_json::to_vec(&request_body)?)?;
 
-        let body = self.base.make_request(builder).await?;
+            let body = self.base.make_cached_request(request, &DETECT_LANGUAGE, request_body.q.iter().map(|q| q.chars().count()).sum()).await?;
 
-        Ok(serde_json::from_str::<DetectLanguageResponse>(&body)?)
+            Ok(serde_json::from_str::<DetectLanguageResponse>(&body)?)
 
+        }).await
     }
 }
 
@@ -38,9 +52,9 @@ struct DetectLanguageRequest {
 
 impl DetectLanguageRequest {
     fn new(text: Vec<&str>) -> Self {
-        return Self{
+        Self{
             q: text.into_iter().map(|s| s.to_owned()).collect(),
-        };
+        }
     }
 }
 
@@ -0,0 +1,42 @@
+use super::{TranslateService, TRANSLATE_SERVICE_ENDPOINT, TRANSLATE_SERVICE_ENDPOINT_ENV};
+use crate::services::ApiMethod;
+use crate::services::request_options::RequestOptions;
+use crate::services::operation::Operation;
+
+use anyhow::Result;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+
+static GET_OPERATION: ApiMethod = ApiMethod { service: "translate", name: "get_operation", idempotent: true };
+
+impl TranslateService {
+
+
+    /// Gets a long-running operation of Cloud Translation v3, such as a batch translation or a glossary creation. <br>
+    /// See https://cloud.google.com/translate/docs/reference/rest/v3/projects.locations.operations/get
+    ///
+    /// * `name` -  name of the operation, such as `projects/my-project/locations/us-central1/operations/1234`.
+    pub async fn get_operation<T: DeserializeOwned, M: DeserializeOwned>(&mut self, name: &str) -> Result<Operation<T, M>> {
+        self.get_operation_with_options(name, &RequestOptions::default()).await
+    }
+
+    /// Gets a long-running operation of Cloud Translation v3, with per-request options such as a timeout. <br>
+    /// Arguments are the same as `get_operation`.
+    ///
+    /// * `options` -  `RequestOptions` of the call.
+    #[tracing::instrument(skip_all, fields(method = "get_operation", endpoint, status, latency_ms, retry_count, response_size))]
+    pub async fn get_operation_with_options<T: DeserializeOwned, M: DeserializeOwned>(&mut self, name: &str, options: &RequestOptions) -> Result<Operation<T, M>> {
+        options.run(async {
+
+            let root = self.base.endpoint_url(TRANSLATE_SERVICE_ENDPOINT, TRANSLATE_SERVICE_ENDPOINT_ENV, "v3/")?;
+            let mut headers = self.base.create_headers().await?;
+            options.apply_headers(&mut headers)?;
+            let request = self.base.build_request(Method::GET, root.join(name)?, headers, vec![])?;
+
+            let body = self.base.make_request(request, &GET_OPERATION).await?;
+
+            Operation::new(self.base.clone(), root, GET_OPERATION.service, serde_json::from_str(&body)?)
+
+        }).await
+    }
+}
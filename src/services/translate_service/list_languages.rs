@@ -1,9 +1,13 @@
 
-use super::{TranslateServiceV2Type, TranslateService, TRANSLATE_SERVICE_BASE_URL};
+use super::{TranslateServiceV2Type, TranslateService};
+use crate::services::ApiMethod;
+use crate::services::request_options::RequestOptions;
 
 use serde::{Serialize, Deserialize};
 use anyhow::Result;
-use reqwest::{Client, Url};
+use reqwest::{header::HeaderMap, Method};
+
+static LIST_LANGUAGES: ApiMethod = ApiMethod { service: "translate", name: "list_languages", idempotent: true };
 
 impl TranslateService {
 
@@ -12,25 +16,38 @@ impl TranslateService {
     /// See https://cloud.google.com/translate/docs/basic/discovering-supported-languages
     ///
     /// * `target` - The target language code for the results.
-    /// If specified, then the language names are returned in the name field of the response, localized in the target language.
-    /// If you do not supply a target language, then the name field is omitted from the response and only the language codes are returned.
-    ///  * `model` - The supported languages for a particular translation model.
-    /// For Cloud Translation - Basic, the value can be nmt to return languages supported by the Neural Machine Translation (NMT) model.
+    ///   If specified, then the language names are returned in the name field of the response, localized in the target language.
+    ///   If you do not supply a target language, then the name field is omitted from the response and only the language codes are returned.
+    /// * `model` - The supported languages for a particular translation model.
+    ///   For Cloud Translation - Basic, the value can be nmt to return languages supported by the Neural Machine Translation (NMT) model.
     pub async fn list_languages(&mut self, target: Option<&str>, model: Option<&str>) -> Result<ListLanguageResponse>{
+        self.list_languages_with_options(target, model, &RequestOptions::default()).await
+    }
 
-        let base_url = Url::parse(&format!("{}/v2/{}", TRANSLATE_SERVICE_BASE_URL, TranslateServiceV2Type::Languages.path()))?;
-        let headers = self.base.create_headers().await?;
-        let request_query = ListLanguageRequest::new(target, model);
+    /// List supported languages, with per-request options such as a timeout. <br>
+    /// Arguments are the same as `list_languages`.
+    ///
+    /// * `options` -  `RequestOptions` of the call.
+    #[tracing::instrument(skip_all, fields(method = "list_languages", endpoint, status, latency_ms, retry_count, response_size))]
+    pub async fn list_languages_with_options(&mut self, target: Option<&str>, model: Option<&str>, options: &RequestOptions) -> Result<ListLanguageResponse>{
+        options.run(async {
 
-        let builder = Client::new().get(base_url)
-                .query(&request_query)
-                .headers(headers);
+            let mut base_url = self.url(TranslateServiceV2Type::Languages)?;
+            let mut headers = HeaderMap::new();
+            options.apply_headers(&mut headers)?;
+            let request_query = ListLanguageRequest::new(target, model);
 
-        let body = self.base.make_request(builder).await?;
+            let query = serde_urlencoded::to_string(&request_query)?;
+            if !query.is_empty() {
+                base_url.set_query(Some(&query));
+            }
+            let request = self.base.build_request(Method::GET, base_url, headers, vec![])?;
 
+            let body = self.base.make_cached_request(request, &LIST_LANGUAGES, 0).await?;
 
-        Ok(serde_json::from_str::<ListLanguageResponse>(&body)?)
+            Ok(serde_json::from_str::<ListLanguageResponse>(&body)?)
 
+        }).await
     }
 }
 
@@ -46,10 +63,10 @@ struct ListLanguageRequest {
 
 impl ListLanguageRequest {
     fn new(target: Option<&str>, model: Option<&str>) -> Self {
-        return Self{
+        Self{
             target: target.map(String::from),
             model: model.map(String::from),
-        };
+        }
     }
 }
 
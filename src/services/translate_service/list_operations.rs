@@ -0,0 +1,35 @@
+use super::{TranslateService, TRANSLATE_SERVICE_ENDPOINT, TRANSLATE_SERVICE_ENDPOINT_ENV};
+use crate::services::ApiMethod;
+use crate::services::operation::Operation;
+use crate::services::pagination::Paginator;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+
+static LIST_OPERATIONS: ApiMethod = ApiMethod { service: "translate", name: "list_operations", idempotent: true };
+
+impl TranslateService {
+
+
+    /// Lists the long-running operations of Cloud Translation v3 in a location. <br>
+    /// See https://cloud.google.com/translate/docs/reference/rest/v3/projects.locations.operations/list
+    ///
+    /// * `name` -  name of the location, such as `projects/my-project/locations/us-central1`.
+    /// * `filter` -  Optional filter, such as `state=RUNNING`.
+    pub fn list_operations<T, M>(&self, name: &str, filter: Option<&str>) -> Result<Paginator<Operation<T, M>>>
+    where
+        T: DeserializeOwned + 'static,
+        M: DeserializeOwned + 'static,
+    {
+        let root = self.base.endpoint_url(TRANSLATE_SERVICE_ENDPOINT, TRANSLATE_SERVICE_ENDPOINT_ENV, "v3/")?;
+        let mut url = root.join(&format!("{}/operations", name))?;
+        if let Some(filter) = filter {
+            url.query_pairs_mut().append_pair("filter", filter);
+        }
+
+        let base = self.base.clone();
+        Ok(self.base.paginate(url, "operations", &LIST_OPERATIONS, move |operation| {
+            Operation::new(base.clone(), root.clone(), LIST_OPERATIONS.service, operation)
+        }))
+    }
+}
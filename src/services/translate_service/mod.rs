@@ -2,14 +2,32 @@
 pub mod translate_text;
 pub mod list_languages;
 pub mod detect_language;
+pub mod translate_api;
+pub mod get_operation;
+pub mod list_operations;
 
 
+use std::sync::Arc;
+
+use anyhow::Result;
+use reqwest::{Client, Url};
+
 use crate::auth::service_account::ServiceAccountCredentials;
+use crate::auth::watched_credentials::WatchedServiceAccountCredentials;
+use crate::auth::delegated_credentials_pool::DelegatedCredentials;
 use super::ServiceBase;
+use super::client_config::ClientConfig;
+use super::retry_policy::RetryPolicy;
+use super::rate_limiter::RateLimiter;
+use super::response_cache::ResponseCache;
+use super::http_service::{BoxError, HttpRequest, HttpResponse, HttpService};
+use super::http_transport::HttpTransport;
 
 
 static TRANSLATE_SERVICE_SCOPE: &str = "https://www.googleapis.com/auth/cloud-translation";
-static TRANSLATE_SERVICE_BASE_URL: &str = "https://translation.googleapis.com/language/translate";
+static TRANSLATE_SERVICE_ENDPOINT: &str = "https://translation.googleapis.com";
+static TRANSLATE_SERVICE_ENDPOINT_ENV: &str = "GOOGLE_TRANSLATE_ENDPOINT";
+static TRANSLATE_SERVICE_PATH: &str = "language/translate";
 
 #[derive(Debug, Clone)]
 pub struct TranslateService {
@@ -22,14 +40,113 @@ impl TranslateService {
     ///
     /// * `api_key` -  API key to use to authenticate to Google Cloud APIs and services that support API keys.
     pub fn new_with_api_key(api_key: String) -> Self {
-        return Self { base: ServiceBase::new_with_api_key(api_key) }
+        Self { base: ServiceBase::new_with_api_key(api_key) }
     }
 
     /// Create `TranslateService` Authenticate by using API keys.
     ///
     /// * `service_account_credentials` -  `ServiceAccountCredentials` to use to authenticate to Google Cloud APIs.
     pub fn new_with_credentials(service_account_credentials: ServiceAccountCredentials) -> Self {
-        return Self { base: ServiceBase::new_with_credentials(service_account_credentials, vec![TRANSLATE_SERVICE_SCOPE]) }
+        Self { base: ServiceBase::new_with_credentials(service_account_credentials, vec![TRANSLATE_SERVICE_SCOPE]) }
+    }
+
+    /// Create `TranslateService` Authenticate by using a watched service account key file.
+    /// The service switches to the new key when the file is rotated on disk.
+    ///
+    /// * `watched_credentials` -  `WatchedServiceAccountCredentials` to use to authenticate to Google Cloud APIs.
+    pub fn new_with_watched_credentials(watched_credentials: WatchedServiceAccountCredentials) -> Self {
+        Self { base: ServiceBase::new_with_watched_credentials(watched_credentials, vec![TRANSLATE_SERVICE_SCOPE]) }
+    }
+
+    /// Create `TranslateService` Authenticate by using delegated credentials from a `DelegatedCredentialsPool`.
+    /// Tokens are cached in the pool per subject, so creating a service per user does not require a new token exchange.
+    ///
+    /// * `delegated_credentials` -  `DelegatedCredentials` to use to authenticate to Google Cloud APIs.
+    pub fn new_with_delegated_credentials(delegated_credentials: DelegatedCredentials) -> Self {
+        Self { base: ServiceBase::new_with_delegated_credentials(delegated_credentials, vec![TRANSLATE_SERVICE_SCOPE]) }
+    }
+
+    /// Use the given HTTP client for all requests of the service, including token requests.
+    /// Clients are cheap to clone and share their connection pool.
+    ///
+    /// * `client` -  `reqwest::Client` to use.
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.base = self.base.with_http_client(client);
+        self
+    }
+
+    /// Use an HTTP client built from the given config for all requests of the service, including token requests.
+    ///
+    /// * `config` -  `ClientConfig` to build the client from.
+    pub fn with_client_config(mut self, config: &ClientConfig) -> Result<Self> {
+        self.base = self.base.with_client_config(config)?;
+        Ok(self)
+    }
+
+    /// Retry transient failures (429, 503, 504 and connection errors) according to the policy.
+    /// By default requests are sent once.
+    ///
+    /// * `retry_policy` -  `RetryPolicy` to use.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.base = self.base.with_retry_policy(retry_policy);
+        self
+    }
+
+    /// Wait for the rate limiter before sending each request, including retries.
+    /// Share the limiter between services that use the same project quota.
+    /// The character budget of the limiter applies to `translate` and `detect_language`.
+    ///
+    /// * `rate_limiter` -  `RateLimiter` to use.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.base = self.base.with_rate_limiter(rate_limiter);
+        self
+    }
+
+    /// Return cached responses for identical requests instead of sending them again.
+    /// Share the cache between clones of the service to share their responses.
+    ///
+    /// * `response_cache` -  `ResponseCache` to use.
+    pub fn with_response_cache(mut self, response_cache: ResponseCache) -> Self {
+        self.base = self.base.with_response_cache(response_cache);
+        self
+    }
+
+    /// Add a `tower` layer, such as a timeout, concurrency limit or logging layer, to the HTTP stack of the service.
+    /// Layers added last see requests first. Clones of the service share one instance of the stack,
+    /// so stateful layers such as `RateLimitLayer` and `ConcurrencyLimitLayer` limit all their requests.
+    ///
+    /// * `layer` -  `tower::Layer` wrapping an `HttpService`.
+    pub fn with_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<HttpService> + Send + Sync + 'static,
+        L::Service: tower::Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Send + 'static,
+        <L::Service as tower::Service<HttpRequest>>::Future: Send + 'static,
+    {
+        self.base = self.base.with_layer(layer);
+        self
+    }
+
+    /// Send requests with the given transport instead of `reqwest`, for example hyper directly or an in-memory transport for tests.
+    /// Token requests of service account credentials are still sent with the `reqwest::Client`.
+    ///
+    /// * `transport` -  `HttpTransport` to send requests with.
+    pub fn with_transport<T: HttpTransport + 'static>(mut self, transport: T) -> Self {
+        self.base = self.base.with_transport(Arc::new(transport));
+        self
+    }
+
+    /// Send requests to the given endpoint instead of `https://translation.googleapis.com`,
+    /// for example a Private Service Connect address, a proxy or a local test server.
+    /// The endpoint can also be set with the `GOOGLE_TRANSLATE_ENDPOINT` environment variable.
+    ///
+    /// * `endpoint` -  Base URL of the API. Any path is kept as a prefix of the API paths.
+    pub fn with_endpoint(mut self, endpoint: Url) -> Self {
+        self.base = self.base.with_endpoint(endpoint);
+        self
+    }
+
+    fn url(&self, method: TranslateServiceV2Type) -> Result<Url> {
+        self.base.endpoint_url(TRANSLATE_SERVICE_ENDPOINT, TRANSLATE_SERVICE_ENDPOINT_ENV, &format!("{}/v2/{}", TRANSLATE_SERVICE_PATH, method.path()))
     }
 }
 
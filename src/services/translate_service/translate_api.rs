@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::TranslateService;
+use crate::services::request_options::RequestOptions;
+use super::detect_language::DetectLanguageResponse;
+use super::list_languages::ListLanguageResponse;
+use super::translate_text::TranslateTextResponse;
+
+
+/// The Cloud Translation Basic Edition (v2) methods of `TranslateService`.
+///
+/// Depend on this trait instead of `TranslateService` to substitute the service in tests.
+/// With the `mockall` feature, `MockTranslateApi` implements it.
+#[cfg_attr(feature = "mockall", mockall::automock)]
+#[async_trait]
+pub trait TranslateApi: Send {
+    /// Translates input text, returning translated text. See `TranslateService::translate`.
+    async fn translate<'a>(&mut self, text: Vec<&'a str>, target: &str, params: Option<HashMap<String, Value>>) -> Result<TranslateTextResponse>;
+
+    /// Detects the language of texts. See `TranslateService::detect_language`.
+    async fn detect_language<'a>(&mut self, text: Vec<&'a str>) -> Result<DetectLanguageResponse>;
+
+    /// Returns a list of supported languages for translation. See `TranslateService::list_languages`.
+    async fn list_languages<'a>(&mut self, target: Option<&'a str>, model: Option<&'a str>) -> Result<ListLanguageResponse>;
+
+    /// `translate` with per-request options. See `TranslateService::translate_with_options`.
+    async fn translate_with_options<'a>(&mut self, text: Vec<&'a str>, target: &str, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<TranslateTextResponse>;
+
+    /// `detect_language` with per-request options. See `TranslateService::detect_language_with_options`.
+    async fn detect_language_with_options<'a>(&mut self, text: Vec<&'a str>, options: &RequestOptions) -> Result<DetectLanguageResponse>;
+
+    /// `list_languages` with per-request options. See `TranslateService::list_languages_with_options`.
+    async fn list_languages_with_options<'a>(&mut self, target: Option<&'a str>, model: Option<&'a str>, options: &RequestOptions) -> Result<ListLanguageResponse>;
+}
+
+#[async_trait]
+impl TranslateApi for TranslateService {
+    async fn translate<'a>(&mut self, text: Vec<&'a str>, target: &str, params: Option<HashMap<String, Value>>) -> Result<TranslateTextResponse> {
+        TranslateService::translate(self, text, target, params).await
+    }
+
+    async fn detect_language<'a>(&mut self, text: Vec<&'a str>) -> Result<DetectLanguageResponse> {
+        TranslateService::detect_language(self, text).await
+    }
+
+    async fn list_languages<'a>(&mut self, target: Option<&'a str>, model: Option<&'a str>) -> Result<ListLanguageResponse> {
+        TranslateService::list_languages(self, target, model).await
+    }
+
+    async fn translate_with_options<'a>(&mut self, text: Vec<&'a str>, target: &str, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<TranslateTextResponse> {
+        TranslateService::translate_with_options(self, text, target, params, options).await
+    }
+
+    async fn detect_language_with_options<'a>(&mut self, text: Vec<&'a str>, options: &RequestOptions) -> Result<DetectLanguageResponse> {
+        TranslateService::detect_language_with_options(self, text, options).await
+    }
+
+    async fn list_languages_with_options<'a>(&mut self, target: Option<&'a str>, model: Option<&'a str>, options: &RequestOptions) -> Result<ListLanguageResponse> {
+        TranslateService::list_languages_with_options(self, target, model, options).await
+    }
+}
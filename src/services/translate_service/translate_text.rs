@@ -1,12 +1,16 @@
 use std::collections::HashMap;
 
-use super::{TranslateServiceV2Type, TranslateService, TRANSLATE_SERVICE_BASE_URL};
+use super::{TranslateServiceV2Type, TranslateService};
+use crate::services::ApiMethod;
+use crate::services::request_options::RequestOptions;
 
 use serde::{Serialize, Deserialize};
 use anyhow::Result;
-use reqwest::{Client, Url};
+use reqwest::{header::HeaderMap, Method};
 use serde_json::Value;
 
+static TRANSLATE: ApiMethod = ApiMethod { service: "translate", name: "translate", idempotent: true };
+
 impl TranslateService {
 
     /// Translates text into the target language. <br>
@@ -21,22 +25,28 @@ impl TranslateService {
     ///     * `source` - The language of the source text.
     ///     * `model` - The translation model. Cloud Translation - Basic offers only the nmt Neural Machine Translation (NMT) model. If the model is base, the request is translated by using the NMT model.
     pub async fn translate(&mut self, text: Vec<&str>, target: &str, params: Option<HashMap<String, Value>>) -> Result<TranslateTextResponse>{
-        let request_body =TranslateTextRequest::new(text, target, params)?;
-        self.post_translate_request(request_body).await
+        self.translate_with_options(text, target, params, &RequestOptions::default()).await
     }
 
-    async fn post_translate_request(&mut self, request_body: TranslateTextRequest) -> Result<TranslateTextResponse> {
+    /// Translates text into the target language, with per-request options such as a timeout. <br>
+    /// Arguments are the same as `translate`.
+    ///
+    /// * `options` -  `RequestOptions` of the call.
+    #[tracing::instrument(skip_all, fields(method = "translate", endpoint, status, latency_ms, retry_count, response_size))]
+    pub async fn translate_with_options(&mut self, text: Vec<&str>, target: &str, params: Option<HashMap<String, Value>>, options: &RequestOptions) -> Result<TranslateTextResponse>{
+        options.run(async {
 
-        let base_url = Url::parse(&format!("{}/v2/{}", TRANSLATE_SERVICE_BASE_URL, TranslateServiceV2Type::Translate.path()))?;
-        let headers = self.base.create_headers().await?;
-        let builder = Client::new().post(base_url)
-                .headers(headers)
-                .body(serde_json::to_string(&request_body)?);
+            let request_body = TranslateTextRequest::new(text, target, params)?;
+            let base_url = self.url(TranslateServiceV2Type::Translate)?;
+            let mut headers = HeaderMap::new();
+            options.apply_headers(&mut headers)?;
+            let request = self.base.build_request(Method::POST, base_url, headers, serde_json::to_vec(&request_body)?)?;
 
-        let body = self.base.make_request(builder).await?;
+            let body = self.base.make_cached_request(request, &TRANSLATE, request_body.q.iter().map(|q| q.chars().count()).sum()).await?;
 
-        Ok(serde_json::from_str::<TranslateTextResponse>(&body)?)
+            Ok(serde_json::from_str::<TranslateTextResponse>(&body)?)
 
+        }).await
     }
 }
 
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+
+/// Tree of the paths in a field mask. A node without children keeps the whole field.
+#[derive(Debug, Default)]
+struct MaskNode {
+    children: HashMap<String, MaskNode>,
+}
+
+impl MaskNode {
+    fn parse(mask: &str) -> Self {
+        let mut root = Self::default();
+        for path in mask.split(',').map(str::trim).filter(|path| !path.is_empty()) {
+            let mut node = &mut root;
+            for segment in path.split('.') {
+                node = node.children.entry(segment.to_owned()).or_default();
+            }
+        }
+        root
+    }
+
+    fn filter(&self, value: Value) -> Value {
+        if self.children.is_empty() || self.children.contains_key("*") {
+            return value;
+        }
+        match value {
+            Value::Array(items) => Value::Array(items.into_iter().map(|item| self.filter(item)).collect()),
+            Value::Object(fields) => {
+                let filtered: Map<String, Value> = fields
+                    .into_iter()
+                    .filter_map(|(name, field)| self.children.get(&name).map(|child| (name, child.filter(field))))
+                    .collect();
+                Value::Object(filtered)
+            }
+            value => value,
+        }
+    }
+}
+
+/// Keep only the fields listed in the field mask, such as `routes.duration,routes.legs.distanceMeters`.
+/// `*` keeps every field. Paths apply to each element of arrays, like the Routes API does for `computeRouteMatrix`.
+pub(super) fn apply(value: Value, mask: &str) -> Value {
+    MaskNode::parse(mask).filter(value)
+}
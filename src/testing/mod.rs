@@ -0,0 +1,344 @@
+//! A local fake of the Cloud Translation and Routes APIs, to run `TranslateService` and `RouteService` in tests without network.
+//!
+//! Translations are pseudo-localized, routes are synthetic with haversine distances, and `X-Goog-FieldMask` is honored.
+//! Errors can be injected per endpoint with `FakeGoogleServer::inject_error`.
+
+mod field_mask;
+mod routes;
+mod translate;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use hyper::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use reqwest::Url;
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+
+static TRANSLATE_PATH: &str = "/language/translate/v2";
+static DETECT_LANGUAGE_PATH: &str = "/language/translate/v2/detect";
+static LIST_LANGUAGES_PATH: &str = "/language/translate/v2/languages";
+static COMPUTE_ROUTES_PATH: &str = "/directions/v2:computeRoutes";
+static COMPUTE_ROUTE_MATRIX_PATH: &str = "/distanceMatrix/v2:computeRouteMatrix";
+static TOKEN_PATH: &str = "/token";
+static FIELD_MASK_HEADER: &str = "X-Goog-FieldMask";
+static API_KEY_HEADER: &str = "X-goog-api-key";
+static TOKEN_LIFETIME_SECONDS: i64 = 3600;
+
+
+/// Endpoints served by `FakeGoogleServer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FakeEndpoint {
+    Translate,
+    DetectLanguage,
+    ListLanguages,
+    ComputeRoutes,
+    ComputeRouteMatrix,
+    /// The OAuth 2.0 token endpoint, at `/token`.
+    Token,
+}
+
+/// An error response returned by `FakeGoogleServer` instead of the fake result.
+#[derive(Debug, Clone)]
+pub struct InjectedError {
+    status_code: u16,
+    message: String,
+    details: Vec<Value>,
+    times: Option<usize>,
+}
+
+impl InjectedError {
+    /// Create `InjectedError` returned for every request until the errors are cleared.
+    ///
+    /// * `status_code` -  HTTP status code of the error, such as 429 or 503. The canonical status name is derived from it.
+    pub fn new(status_code: u16) -> Self {
+        Self { status_code, message: "Injected error".to_owned(), details: vec![], times: None }
+    }
+
+    /// * `message` -  Message of the error.
+    pub fn with_message(mut self, message: &str) -> Self {
+        self.message = message.to_owned();
+        self
+    }
+
+    /// Add an entry to the details of the error, such as a `google.rpc.RetryInfo`.
+    ///
+    /// * `detail` -  Detail with its `@type`.
+    pub fn with_detail(mut self, detail: Value) -> Self {
+        self.details.push(detail);
+        self
+    }
+
+    /// Return the error for the next `times` requests only.
+    ///
+    /// * `times` -  Number of requests to fail.
+    pub fn with_times(mut self, times: usize) -> Self {
+        self.times = Some(times);
+        self
+    }
+}
+
+
+#[derive(Debug, Default)]
+struct ServerState {
+    errors: HashMap<FakeEndpoint, InjectedError>,
+    request_counts: HashMap<FakeEndpoint, usize>,
+    issued_tokens: u64,
+}
+
+/// Fake Cloud Translation and Routes API server. Cloning the server is cheap, and clones share errors and counts.
+///
+/// Served endpoints:
+/// * `POST /language/translate/v2` translates texts to `[target] pseudo-localized text`, such as `[ja] ĥéļļö`.
+/// * `POST /language/translate/v2/detect` detects the language from the script of the texts.
+/// * `GET /language/translate/v2/languages` lists a fixed set of languages.
+/// * `POST /directions/v2:computeRoutes` returns one route with a leg between each pair of stops.
+/// * `POST /distanceMatrix/v2:computeRouteMatrix` returns an element for every origin and destination pair.
+/// * `POST /token` issues fake access tokens, or ID tokens for assertions with a `target_audience`. Point the `token_uri` of test service account credentials at it.
+///
+/// API requests need an API key or a bearer token, and Routes requests need `X-Goog-FieldMask`, as with the real APIs.
+#[derive(Debug, Clone, Default)]
+pub struct FakeGoogleServer {
+    state: Arc<Mutex<ServerState>>,
+}
+
+/// A `FakeGoogleServer` running in the background. The server is stopped when the handle is dropped.
+#[derive(Debug)]
+pub struct FakeGoogleServerHandle {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl FakeGoogleServerHandle {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL of the server, to pass to `with_endpoint` of the services.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.addr)).expect("socket address is a valid URL")
+    }
+
+    /// URL of the fake token endpoint, to use as the `token_uri` of service account credentials.
+    pub fn token_uri(&self) -> String {
+        format!("http://{}{}", self.addr, TOKEN_PATH)
+    }
+}
+
+impl Drop for FakeGoogleServerHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl FakeGoogleServer {
+    /// Create `FakeGoogleServer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the error instead of the fake result for requests to the endpoint.
+    ///
+    /// * `endpoint` -  Endpoint to fail.
+    /// * `error` -  `InjectedError` to return.
+    pub fn inject_error(&self, endpoint: FakeEndpoint, error: InjectedError) {
+        self.lock().errors.insert(endpoint, error);
+    }
+
+    /// Remove all the injected errors.
+    pub fn clear_errors(&self) {
+        self.lock().errors.clear();
+    }
+
+    /// Number of requests received by the endpoint, including failed ones.
+    ///
+    /// * `endpoint` -  Endpoint to count requests of.
+    pub fn request_count(&self, endpoint: FakeEndpoint) -> usize {
+        self.lock().request_counts.get(&endpoint).copied().unwrap_or_default()
+    }
+
+    /// Start serving on a free port of the loopback interface, in the background. Must be called within a Tokio runtime.
+    pub fn start(&self) -> Result<FakeGoogleServerHandle> {
+        let (addr, server) = self.bind(&SocketAddr::from(([127, 0, 0, 1], 0)))?;
+        let task = tokio::spawn(async move {
+            if let Err(error) = server.await {
+                tracing::error!(error = %error, "fake Google server failed");
+            }
+        });
+        Ok(FakeGoogleServerHandle { addr, task })
+    }
+
+    /// Serve requests on the given address until the process is stopped.
+    ///
+    /// * `addr` -  Address to listen on.
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let (_, server) = self.bind(&addr)?;
+        server.await?;
+        Ok(())
+    }
+
+    fn bind(&self, addr: &SocketAddr) -> Result<(SocketAddr, impl Future<Output = hyper::Result<()>> + Send)> {
+        let server = self.clone();
+        let make_service = make_service_fn(move |_| {
+            let server = server.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle(request).await) }
+                }))
+            }
+        });
+
+        let server = Server::try_bind(addr)?.serve(make_service);
+        Ok((server.local_addr(), server))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ServerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let (parts, body) = request.into_parts();
+        let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+
+        let path = parts.uri.path().trim_end_matches('/');
+        let endpoint = match (&parts.method, path) {
+            (&Method::POST, path) if path == TRANSLATE_PATH => FakeEndpoint::Translate,
+            (&Method::POST, path) if path == DETECT_LANGUAGE_PATH => FakeEndpoint::DetectLanguage,
+            (&Method::GET, path) if path == LIST_LANGUAGES_PATH => FakeEndpoint::ListLanguages,
+            (&Method::POST, path) if path == COMPUTE_ROUTES_PATH => FakeEndpoint::ComputeRoutes,
+            (&Method::POST, path) if path == COMPUTE_ROUTE_MATRIX_PATH => FakeEndpoint::ComputeRouteMatrix,
+            (&Method::POST, path) if path == TOKEN_PATH => FakeEndpoint::Token,
+            _ => return error_response(StatusCode::NOT_FOUND, &format!("{} {} not found", parts.method, path), vec![]),
+        };
+
+        if let Some(error) = self.count_request(endpoint) {
+            let status = StatusCode::from_u16(error.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            return error_response(status, &error.message, error.details);
+        }
+        if endpoint == FakeEndpoint::Token {
+            return self.token_response(&body);
+        }
+        if !is_authenticated(&parts.headers) {
+            return error_response(StatusCode::UNAUTHORIZED, "Request is missing an API key or a bearer token.", vec![]);
+        }
+
+        let field_mask = parts.headers.get(FIELD_MASK_HEADER).and_then(|mask| mask.to_str().ok());
+        let query: HashMap<String, String> = parts.uri.query()
+            .map(|query| serde_urlencoded::from_str(query).unwrap_or_default())
+            .unwrap_or_default();
+
+        let result = match endpoint {
+            FakeEndpoint::Translate => translate::translate(&body),
+            FakeEndpoint::DetectLanguage => translate::detect_language(&body),
+            FakeEndpoint::ListLanguages => Ok(translate::list_languages(&query)),
+            FakeEndpoint::ComputeRoutes | FakeEndpoint::ComputeRouteMatrix => {
+                let Some(field_mask) = field_mask else {
+                    return error_response(StatusCode::BAD_REQUEST, "FieldMask is a required parameter.", vec![]);
+                };
+                let response = if endpoint == FakeEndpoint::ComputeRoutes {
+                    routes::compute_routes(&body)
+                } else {
+                    routes::compute_route_matrix(&body)
+                };
+                response.map(|response| field_mask::apply(response, field_mask))
+            }
+            FakeEndpoint::Token => unreachable!("token requests are answered above"),
+        };
+
+        match result {
+            Ok(response) => json_response(StatusCode::OK, &response),
+            Err(error) => error_response(StatusCode::BAD_REQUEST, &error.to_string(), vec![]),
+        }
+    }
+
+    /// Count the request, and take the error injected for the endpoint if any.
+    fn count_request(&self, endpoint: FakeEndpoint) -> Option<InjectedError> {
+        let mut state = self.lock();
+        *state.request_counts.entry(endpoint).or_default() += 1;
+
+        let error = state.errors.get_mut(&endpoint)?;
+        let injected = error.clone();
+        match error.times {
+            Some(0) => None,
+            Some(times) => {
+                error.times = Some(times - 1);
+                Some(injected)
+            }
+            None => Some(injected),
+        }
+    }
+
+    /// Issue an access token, or an ID token if the assertion has a `target_audience`, as the Google token endpoint does.
+    fn token_response(&self, body: &[u8]) -> Response<Body> {
+        let mut state = self.lock();
+        state.issued_tokens += 1;
+        if assertion_claims(body).is_some_and(|claims| claims["target_audience"].is_string()) {
+            return json_response(StatusCode::OK, &json!({ "id_token": format!("fake-id-token-{}", state.issued_tokens) }));
+        }
+        let access_token = format!("fake-access-token-{}", state.issued_tokens);
+        json_response(StatusCode::OK, &json!({
+            "access_token": access_token,
+            "expires_in": TOKEN_LIFETIME_SECONDS,
+            "token_type": "Bearer",
+        }))
+    }
+}
+
+
+fn is_authenticated(headers: &HeaderMap) -> bool {
+    let has_api_key = headers.get(API_KEY_HEADER).is_some_and(|key| !key.is_empty());
+    let has_bearer_token = headers
+        .get(AUTHORIZATION)
+        .and_then(|authorization| authorization.to_str().ok())
+        .is_some_and(|authorization| authorization.starts_with("Bearer "));
+    has_api_key || has_bearer_token
+}
+
+// claims of the JWT `assertion` of a token request, without checking the signature
+fn assertion_claims(body: &[u8]) -> Option<Value> {
+    use base64::Engine;
+    let form: HashMap<String, String> = serde_urlencoded::from_bytes(body).ok()?;
+    let payload = form.get("assertion")?.split('.').nth(1)?;
+    let claims = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(payload).ok()?;
+    serde_json::from_slice(&claims).ok()
+}
+
+// canonical status names of https://cloud.google.com/apis/design/errors#handling_errors
+fn status_name(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        400 => "INVALID_ARGUMENT",
+        401 => "UNAUTHENTICATED",
+        403 => "PERMISSION_DENIED",
+        404 => "NOT_FOUND",
+        409 => "ABORTED",
+        429 => "RESOURCE_EXHAUSTED",
+        499 => "CANCELLED",
+        501 => "UNIMPLEMENTED",
+        503 => "UNAVAILABLE",
+        504 => "DEADLINE_EXCEEDED",
+        500..=599 => "INTERNAL",
+        _ => "UNKNOWN",
+    }
+}
+
+fn error_response(status: StatusCode, message: &str, details: Vec<Value>) -> Response<Body> {
+    let mut error = json!({ "code": status.as_u16(), "message": message, "status": status_name(status) });
+    if !details.is_empty() {
+        error["details"] = Value::Array(details);
+    }
+    json_response(status, &json!({ "error": error }))
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json; charset=UTF-8"));
+    response
+}
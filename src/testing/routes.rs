@@ -0,0 +1,179 @@
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+
+
+static EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+// waypoints given by place ID or address are placed around this point
+static ORIGIN_LAT_LNG: (f64, f64) = (37.4220, -122.0841);
+// the Routes API limit for computeRouteMatrix
+static MAX_MATRIX_ELEMENTS: usize = 625;
+
+
+/// One synthetic route through the waypoints, with a leg between each pair of stops.
+/// Distances are the haversine distances between the waypoints, durations assume a constant speed for the travel mode.
+pub(super) fn compute_routes(body: &[u8]) -> Result<Value> {
+    let request: Value = serde_json::from_slice(body)?;
+    let speed = speed(&request);
+
+    let origin = lat_lng(&request["origin"]).map_err(|e| anyhow!("Invalid origin: {}", e))?;
+    let mut path = vec![origin];
+    let mut leg_paths = vec![];
+    let mut leg_path = vec![origin];
+    for intermediate in request["intermediates"].as_array().into_iter().flatten() {
+        let point = lat_lng(intermediate).map_err(|e| anyhow!("Invalid intermediate: {}", e))?;
+        path.push(point);
+        leg_path.push(point);
+        // via waypoints do not split the route into legs
+        if intermediate["via"].as_bool() != Some(true) {
+            leg_paths.push(std::mem::replace(&mut leg_path, vec![point]));
+        }
+    }
+    let destination = lat_lng(&request["destination"]).map_err(|e| anyhow!("Invalid destination: {}", e))?;
+    path.push(destination);
+    leg_path.push(destination);
+    leg_paths.push(leg_path);
+
+    let legs: Vec<Value> = leg_paths
+        .iter()
+        .map(|leg_path| {
+            let distance = distance(leg_path);
+            json!({
+                "distanceMeters": distance,
+                "duration": duration(distance, speed),
+                "staticDuration": duration(distance, speed),
+                "polyline": { "encodedPolyline": encode_polyline(leg_path) },
+                "startLocation": { "latLng": lat_lng_value(leg_path[0]) },
+                "endLocation": { "latLng": lat_lng_value(leg_path[leg_path.len() - 1]) },
+            })
+        })
+        .collect();
+
+    let distance = distance(&path);
+    let (low, high) = bounds(&path);
+    Ok(json!({
+        "routes": [{
+            "routeLabels": ["DEFAULT_ROUTE"],
+            "legs": legs,
+            "distanceMeters": distance,
+            "duration": duration(distance, speed),
+            "staticDuration": duration(distance, speed),
+            "polyline": { "encodedPolyline": encode_polyline(&path) },
+            "viewport": { "low": lat_lng_value(low), "high": lat_lng_value(high) },
+        }]
+    }))
+}
+
+/// Elements for every origin and destination pair, with the haversine distance between them.
+pub(super) fn compute_route_matrix(body: &[u8]) -> Result<Value> {
+    let request: Value = serde_json::from_slice(body)?;
+    let speed = speed(&request);
+
+    let origins = matrix_waypoints(&request["origins"]).map_err(|e| anyhow!("Invalid origins: {}", e))?;
+    let destinations = matrix_waypoints(&request["destinations"]).map_err(|e| anyhow!("Invalid destinations: {}", e))?;
+    if origins.len() * destinations.len() > MAX_MATRIX_ELEMENTS {
+        bail!("The number of elements must not exceed {}.", MAX_MATRIX_ELEMENTS);
+    }
+
+    let mut elements = vec![];
+    for (origin_index, origin) in origins.iter().enumerate() {
+        for (destination_index, destination) in destinations.iter().enumerate() {
+            let distance = distance(&[*origin, *destination]);
+            elements.push(json!({
+                "originIndex": origin_index,
+                "destinationIndex": destination_index,
+                "status": {},
+                "condition": "ROUTE_EXISTS",
+                "distanceMeters": distance,
+                "duration": duration(distance, speed),
+                "staticDuration": duration(distance, speed),
+            }));
+        }
+    }
+    Ok(Value::Array(elements))
+}
+
+fn matrix_waypoints(value: &Value) -> Result<Vec<(f64, f64)>> {
+    let waypoints = value.as_array().filter(|waypoints| !waypoints.is_empty()).ok_or(anyhow!("at least one is required"))?;
+    waypoints.iter().map(|waypoint| lat_lng(&waypoint["waypoint"])).collect()
+}
+
+fn lat_lng(waypoint: &Value) -> Result<(f64, f64)> {
+    if let Some(lat_lng) = waypoint.get("location").map(|location| &location["latLng"]) {
+        let latitude = lat_lng["latitude"].as_f64().unwrap_or_default();
+        let longitude = lat_lng["longitude"].as_f64().unwrap_or_default();
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            bail!("latitude must be in [-90, 90] and longitude in [-180, 180]");
+        }
+        return Ok((latitude, longitude));
+    }
+    match waypoint["placeId"].as_str().or(waypoint["address"].as_str()) {
+        Some(place) => Ok(place_lat_lng(place)),
+        None => bail!("one of location, placeId or address is required"),
+    }
+}
+
+// a stable position for a place ID or address, within about 50km of ORIGIN_LAT_LNG
+fn place_lat_lng(place: &str) -> (f64, f64) {
+    // FNV-1a, stable across Rust versions unlike DefaultHasher
+    let hash = place.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3));
+    let latitude_offset = (hash & 0xFFFF) as f64 / 65535.0 - 0.5;
+    let longitude_offset = ((hash >> 16) & 0xFFFF) as f64 / 65535.0 - 0.5;
+    (ORIGIN_LAT_LNG.0 + latitude_offset * 0.9, ORIGIN_LAT_LNG.1 + longitude_offset * 0.9)
+}
+
+fn speed(request: &Value) -> f64 {
+    // meters per second
+    match request["travelMode"].as_str().unwrap_or("DRIVE") {
+        "WALK" => 1.4,
+        "BICYCLE" => 4.2,
+        "TWO_WHEELER" => 11.0,
+        "TRANSIT" => 8.3,
+        _ => 13.9,
+    }
+}
+
+fn duration(distance: u64, speed: f64) -> String {
+    format!("{}s", (distance as f64 / speed).ceil() as u64)
+}
+
+fn distance(path: &[(f64, f64)]) -> u64 {
+    path.windows(2).map(|segment| haversine(segment[0], segment[1])).sum::<f64>().round() as u64
+}
+
+fn haversine(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (from_latitude, to_latitude) = (from.0.to_radians(), to.0.to_radians());
+    let latitude_delta = to_latitude - from_latitude;
+    let longitude_delta = (to.1 - from.1).to_radians();
+    let a = (latitude_delta / 2.0).sin().powi(2) + from_latitude.cos() * to_latitude.cos() * (longitude_delta / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+fn bounds(path: &[(f64, f64)]) -> ((f64, f64), (f64, f64)) {
+    path.iter().fold(((90.0, 180.0), (-90.0, -180.0)), |(low, high), point| {
+        ((low.0.min(point.0), low.1.min(point.1)), (high.0.max(point.0), high.1.max(point.1)))
+    })
+}
+
+fn lat_lng_value(point: (f64, f64)) -> Value {
+    json!({ "latitude": point.0, "longitude": point.1 })
+}
+
+/// Encoded polyline of the points.
+/// See https://developers.google.com/maps/documentation/utilities/polylinealgorithm
+fn encode_polyline(path: &[(f64, f64)]) -> String {
+    let mut encoded = String::new();
+    let mut previous = (0_i64, 0_i64);
+    for point in path {
+        let current = ((point.0 * 1e5).round() as i64, (point.1 * 1e5).round() as i64);
+        for delta in [current.0 - previous.0, current.1 - previous.1] {
+            let mut value = if delta < 0 { !(delta << 1) } else { delta << 1 };
+            while value >= 0x20 {
+                encoded.push(char::from(((0x20 | (value & 0x1f)) + 63) as u8));
+                value >>= 5;
+            }
+            encoded.push(char::from((value + 63) as u8));
+        }
+        previous = current;
+    }
+    encoded
+}
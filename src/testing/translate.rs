@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+
+// languages listed by the fake `languages` endpoint, with their English names
+static LANGUAGES: [(&str, &str); 12] = [
+    ("ar", "Arabic"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("ru", "Russian"),
+    ("th", "Thai"),
+    ("zh", "Chinese"),
+];
+
+
+/// Pseudo-localizes the texts, so `hello` translated to `ja` is `[ja] ĥéļļö`.
+pub(super) fn translate(body: &[u8]) -> Result<Value> {
+    let request: Value = serde_json::from_slice(body)?;
+    let target = request["target"].as_str().ok_or(anyhow!("Target language is required."))?;
+    let texts = texts(&request)?;
+
+    let translations: Vec<Value> = texts
+        .iter()
+        .map(|text| {
+            let mut translation = json!({ "translatedText": format!("[{}] {}", target, pseudo_localize(text)) });
+            if request["source"].as_str().is_none() {
+                translation["detectedSourceLanguage"] = json!(detect(text));
+            }
+            if let Some(model) = request["model"].as_str() {
+                translation["model"] = json!(model);
+            }
+            translation
+        })
+        .collect();
+    Ok(json!({ "data": { "translations": translations } }))
+}
+
+/// Detects the language from the script of the text, and `en` for Latin text.
+pub(super) fn detect_language(body: &[u8]) -> Result<Value> {
+    let request: Value = serde_json::from_slice(body)?;
+    let detections: Vec<Value> = texts(&request)?
+        .iter()
+        .map(|text| json!([{ "language": detect(text), "isReliable": false, "confidence": 1.0 }]))
+        .collect();
+    Ok(json!({ "data": { "detections": detections } }))
+}
+
+/// Lists a fixed set of languages, with their English names if a target is given.
+pub(super) fn list_languages(query: &HashMap<String, String>) -> Value {
+    let languages: Vec<Value> = LANGUAGES
+        .iter()
+        .map(|(language, name)| match query.get("target") {
+            Some(_) => json!({ "language": language, "name": name }),
+            None => json!({ "language": language }),
+        })
+        .collect();
+    json!({ "data": { "languages": languages } })
+}
+
+fn texts(request: &Value) -> Result<Vec<String>> {
+    match &request["q"] {
+        Value::String(text) => Ok(vec![text.to_owned()]),
+        Value::Array(texts) => Ok(texts.iter().filter_map(|text| text.as_str()).map(|text| text.to_owned()).collect()),
+        _ => Err(anyhow!("Required Text: q")),
+    }
+}
+
+// HTML tags are left as they are
+fn pseudo_localize(text: &str) -> String {
+    let mut in_tag = false;
+    text.chars()
+        .map(|c| {
+            match c {
+                '<' => in_tag = true,
+                '>' => in_tag = false,
+                _ => {}
+            }
+            if in_tag {
+                return c;
+            }
+            match c {
+                'a' => 'á', 'c' => 'ç', 'e' => 'é', 'h' => 'ĥ', 'i' => 'î', 'l' => 'ļ', 'n' => 'ñ', 'o' => 'ö', 'u' => 'ü', 'y' => 'ý',
+                'A' => 'Á', 'C' => 'Ç', 'E' => 'É', 'H' => 'Ĥ', 'I' => 'Î', 'L' => 'Ļ', 'N' => 'Ñ', 'O' => 'Ö', 'U' => 'Ü', 'Y' => 'Ý',
+                c => c,
+            }
+        })
+        .collect()
+}
+
+fn detect(text: &str) -> &'static str {
+    text.chars()
+        .find_map(|c| match c as u32 {
+            0x3040..=0x30FF => Some("ja"),
+            0xAC00..=0xD7AF | 0x1100..=0x11FF => Some("ko"),
+            0x4E00..=0x9FFF => Some("zh"),
+            0x0400..=0x04FF => Some("ru"),
+            0x0600..=0x06FF => Some("ar"),
+            0x0370..=0x03FF => Some("el"),
+            0x0590..=0x05FF => Some("he"),
+            0x0E00..=0x0E7F => Some("th"),
+            _ => None,
+        })
+        .unwrap_or("en")
+}
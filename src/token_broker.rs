@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Local;
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use lru::LruCache;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::auth::service_account::ServiceAccountCredentials;
+
+static METADATA_FLAVOR_HEADER: &str = "Metadata-Flavor";
+static METADATA_FLAVOR_VALUE: &str = "Google";
+static SERVICE_ACCOUNTS_PATH: &str = "/computeMetadata/v1/instance/service-accounts/";
+static PROJECT_ID_PATH: &str = "/computeMetadata/v1/project/project-id";
+static DEFAULT_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+// scopes are chosen by the callers, so only the most recently used sets of scopes keep their token
+static MAX_SCOPE_SETS: usize = 32;
+
+
+/// Serves access and ID tokens over a metadata-server-compatible HTTP API.
+///
+/// Clients that support the GCE metadata server (for example, Google client libraries with `GCE_METADATA_HOST` set)
+/// can get tokens from the broker without holding the service account key.
+/// Supported endpoints:
+/// * `GET /computeMetadata/v1/instance/service-accounts/default/token?scopes=...`
+/// * `GET /computeMetadata/v1/instance/service-accounts/default/identity?audience=...`
+/// * `GET /computeMetadata/v1/instance/service-accounts/default/email`
+/// * `GET /computeMetadata/v1/project/project-id`
+pub struct TokenBroker {
+    credentials: ServiceAccountCredentials,
+    // locked per set of scopes, so a slow token request only delays requests for the same scopes
+    scoped_credentials: std::sync::Mutex<LruCache<Vec<String>, Arc<Mutex<ServiceAccountCredentials>>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+    token_type: String,
+}
+
+impl TokenBroker {
+    /// Create `TokenBroker`. Tokens are cached for the 32 most recently requested sets of scopes.
+    ///
+    /// * `credentials` -  `ServiceAccountCredentials` to issue tokens for.
+    pub fn new(credentials: ServiceAccountCredentials) -> Self {
+        let capacity = NonZeroUsize::new(MAX_SCOPE_SETS).expect("MAX_SCOPE_SETS is not 0");
+        Self { credentials, scoped_credentials: std::sync::Mutex::new(LruCache::new(capacity)) }
+    }
+
+    /// Serve requests on the given address until the process is stopped.
+    ///
+    /// * `addr` -  Address to listen on. Use a loopback address so that only processes on the same host can get tokens.
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let (_, server) = self.bind(&addr)?;
+        server.await?;
+        Ok(())
+    }
+
+    fn bind(self, addr: &SocketAddr) -> Result<(SocketAddr, impl Future<Output = hyper::Result<()>> + Send)> {
+        let broker = Arc::new(self);
+        let make_service = make_service_fn(move |_| {
+            let broker = broker.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let broker = broker.clone();
+                    async move { Ok::<_, Infallible>(broker.handle(request).await) }
+                }))
+            }
+        });
+
+        let server = Server::try_bind(addr)?.serve(make_service);
+        Ok((server.local_addr(), server))
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        if request.method() != Method::GET {
+            return text_response(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed".to_owned());
+        }
+        // same check as the metadata server, to block requests forwarded from browsers
+        if request.headers().get(METADATA_FLAVOR_HEADER).and_then(|v| v.to_str().ok()) != Some(METADATA_FLAVOR_VALUE) {
+            return text_response(StatusCode::FORBIDDEN, format!("Missing {}:{} header", METADATA_FLAVOR_HEADER, METADATA_FLAVOR_VALUE));
+        }
+
+        let Ok(url) = Url::parse(&format!("http://localhost{}", request.uri())) else {
+            return text_response(StatusCode::BAD_REQUEST, "Invalid request uri".to_owned());
+        };
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+        let path = url.path();
+        if path == "/" || path == "/computeMetadata/v1/" {
+            return text_response(StatusCode::OK, "".to_owned());
+        }
+        if path == PROJECT_ID_PATH {
+            return text_response(StatusCode::OK, self.credentials.project_id().to_owned());
+        }
+
+        let Some(account_path) = path.strip_prefix(SERVICE_ACCOUNTS_PATH) else {
+            return text_response(StatusCode::NOT_FOUND, "Not found".to_owned());
+        };
+        let Some((account, endpoint)) = account_path.split_once('/') else {
+            return text_response(StatusCode::NOT_FOUND, "Not found".to_owned());
+        };
+        if account != "default" && account != self.credentials.client_email() {
+            return text_response(StatusCode::NOT_FOUND, format!("Unknown service account: {}", account));
+        }
+
+        let result = match endpoint {
+            "email" => Ok(text_response(StatusCode::OK, self.credentials.client_email().to_owned())),
+            "token" => self.token_response(query.get("scopes")).await,
+            "identity" => match query.get("audience") {
+                Some(audience) => self.credentials.get_id_token(audience).await.map(|token| text_response(StatusCode::OK, token)),
+                None => Ok(text_response(StatusCode::BAD_REQUEST, "audience parameter required".to_owned())),
+            },
+            _ => Ok(text_response(StatusCode::NOT_FOUND, "Not found".to_owned())),
+        };
+
+        result.unwrap_or_else(|error| text_response(StatusCode::INTERNAL_SERVER_ERROR, error.to_string()))
+    }
+
+    async fn token_response(&self, scopes: Option<&String>) -> Result<Response<Body>> {
+        let mut scopes: Vec<String> = match scopes {
+            Some(scopes) => scopes.split(',').map(|s| s.trim().to_owned()).filter(|s| !s.is_empty()).collect(),
+            None => vec![DEFAULT_SCOPE.to_owned()],
+        };
+        scopes.sort();
+
+        // tokens are cached per set of scopes
+        let credentials = self.scoped_credentials
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert(scopes.clone(), || Arc::new(Mutex::new(self.credentials.with_scopes(scopes.iter().map(|s| s.as_str()).collect()))))
+            .clone();
+        let mut credentials = credentials.lock().await;
+        let access_token = credentials.get_access_token().await?;
+        let expires_in = credentials.token_expiration_time().unwrap_or_default() - Local::now().timestamp();
+
+        let body = serde_json::to_string(&TokenResponse { access_token, expires_in, token_type: "Bearer".to_owned() })?;
+        let mut response = text_response(StatusCode::OK, body);
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Ok(response)
+    }
+}
+
+
+fn text_response(status: StatusCode, body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response.headers_mut().insert(METADATA_FLAVOR_HEADER, HeaderValue::from_static(METADATA_FLAVOR_VALUE));
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/text"));
+    response
+}
+
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::testing::{FakeEndpoint, FakeGoogleServer, FakeGoogleServerHandle};
+
+    static TOKEN_PATH: &str = "/computeMetadata/v1/instance/service-accounts/default/token";
+    static IDENTITY_PATH: &str = "/computeMetadata/v1/instance/service-accounts/default/identity";
+
+    fn credentials(server: &FakeGoogleServerHandle) -> ServiceAccountCredentials {
+        let credentials = json!({
+            "type": "service_account",
+            "project_id": "test-project",
+            "private_key_id": "test-key-id",
+            "private_key": include_str!("../tests/data/mtls/rsa_key.pem"),
+            "client_email": "broker@test-project.iam.gserviceaccount.com",
+            "client_id": "1234567890",
+            "auth_uri": "https://accounts.google.com/o/oauth2/auth",
+            "token_uri": server.token_uri(),
+            "auth_provider_x509_cert_url": "https://www.googleapis.com/oauth2/v1/certs",
+            "client_x509_cert_url": "https://www.googleapis.com/robot/v1/metadata/x509/broker",
+            "universe_domain": "googleapis.com",
+        });
+        ServiceAccountCredentials::from_service_account_info(credentials.to_string()).unwrap()
+    }
+
+    fn start(credentials: ServiceAccountCredentials) -> String {
+        let (addr, server) = TokenBroker::new(credentials).bind(&SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        tokio::spawn(server);
+        format!("http://{}", addr)
+    }
+
+    async fn get(url: &str) -> reqwest::Response {
+        reqwest::Client::new().get(url).header(METADATA_FLAVOR_HEADER, METADATA_FLAVOR_VALUE).send().await.unwrap()
+    }
+
+    async fn get_json(url: &str) -> Value {
+        serde_json::from_str(&get(url).await.text().await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn token_is_cached_per_set_of_scopes() {
+        let fake = FakeGoogleServer::new();
+        let fake_handle = fake.start().unwrap();
+        let broker = start(credentials(&fake_handle));
+
+        let response = get(&format!("{}{}", broker, TOKEN_PATH)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[METADATA_FLAVOR_HEADER], METADATA_FLAVOR_VALUE);
+        let token: Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        assert_eq!((token["access_token"].as_str(), token["token_type"].as_str()), (Some("fake-access-token-1"), Some("Bearer")));
+        assert!(token["expires_in"].as_i64().is_some_and(|expires_in| expires_in > 0));
+
+        let token = get_json(&format!("{}{}", broker, TOKEN_PATH)).await;
+        assert_eq!(token["access_token"], "fake-access-token-1");
+        let token = get_json(&format!("{}{}?scopes=a,b", broker, TOKEN_PATH)).await;
+        assert_eq!(token["access_token"], "fake-access-token-2");
+        let token = get_json(&format!("{}{}?scopes=b,a", broker, TOKEN_PATH)).await;
+        assert_eq!(token["access_token"], "fake-access-token-2");
+        assert_eq!(fake.request_count(FakeEndpoint::Token), 2);
+    }
+
+    #[tokio::test]
+    async fn identity_token_for_audience() {
+        let fake = FakeGoogleServer::new();
+        let fake_handle = fake.start().unwrap();
+        let broker = start(credentials(&fake_handle));
+
+        let response = get(&format!("{}{}?audience=https://example.com", broker, IDENTITY_PATH)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "fake-id-token-1");
+
+        let response = get(&format!("{}{}", broker, IDENTITY_PATH)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn requests_without_metadata_flavor_are_rejected() {
+        let fake = FakeGoogleServer::new();
+        let fake_handle = fake.start().unwrap();
+        let broker = start(credentials(&fake_handle));
+
+        let response = reqwest::Client::new().get(format!("{}{}", broker, TOKEN_PATH)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(fake.request_count(FakeEndpoint::Token), 0);
+    }
+
+    #[tokio::test]
+    async fn email_project_id_and_unknown_accounts() {
+        let fake = FakeGoogleServer::new();
+        let fake_handle = fake.start().unwrap();
+        let broker = start(credentials(&fake_handle));
+
+        let email = get(&format!("{}/computeMetadata/v1/instance/service-accounts/default/email", broker)).await;
+        assert_eq!(email.text().await.unwrap(), "broker@test-project.iam.gserviceaccount.com");
+        let project_id = get(&format!("{}{}", broker, PROJECT_ID_PATH)).await;
+        assert_eq!(project_id.text().await.unwrap(), "test-project");
+        let unknown = get(&format!("{}/computeMetadata/v1/instance/service-accounts/other@example.com/token", broker)).await;
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn scope_sets_are_bounded() {
+        let fake = FakeGoogleServer::new();
+        let fake_handle = fake.start().unwrap();
+        let broker = TokenBroker::new(credentials(&fake_handle));
+
+        for scope in 0..=MAX_SCOPE_SETS {
+            broker.token_response(Some(&format!("scope-{}", scope))).await.unwrap();
+        }
+        let scoped_credentials = broker.scoped_credentials.lock().unwrap();
+        assert_eq!(scoped_credentials.len(), MAX_SCOPE_SETS);
+        assert!(!scoped_credentials.contains(&vec!["scope-0".to_owned()]));
+    }
+}
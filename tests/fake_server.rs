@@ -0,0 +1,231 @@
+#![cfg(all(feature = "testing", feature = "translate", feature = "routes"))]
+
+//! `TranslateService` and `RouteService` against `FakeGoogleServer`.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use google_api_rust_client_unoffical::auth::service_account::ServiceAccountCredentials;
+use google_api_rust_client_unoffical::services::response_cache::ResponseCache;
+use google_api_rust_client_unoffical::services::retry_policy::RetryPolicy;
+use google_api_rust_client_unoffical::services::route_service::common_models::{Location, WayPoint};
+use google_api_rust_client_unoffical::services::route_service::get_route_matrix::request_model::RouteMatrixOrigin;
+use google_api_rust_client_unoffical::services::route_service::RouteService;
+use google_api_rust_client_unoffical::services::service_error::ServiceError;
+use google_api_rust_client_unoffical::services::translate_service::TranslateService;
+use google_api_rust_client_unoffical::testing::{FakeEndpoint, FakeGoogleServer, FakeGoogleServerHandle, InjectedError};
+use serde_json::json;
+
+
+static SERVICE_ACCOUNT_KEY: &str = include_str!("data/mtls/rsa_key.pem");
+
+fn retry_policy() -> RetryPolicy {
+    RetryPolicy::new().with_initial_backoff(Duration::from_millis(1)).with_jitter(false)
+}
+
+fn translate_service(handle: &FakeGoogleServerHandle, api_key: &str) -> TranslateService {
+    TranslateService::new_with_api_key(api_key.to_owned()).with_endpoint(handle.url()).with_retry_policy(retry_policy())
+}
+
+fn route_service(handle: &FakeGoogleServerHandle) -> RouteService {
+    RouteService::new_with_api_key("test-api-key".to_owned()).with_endpoint(handle.url()).with_retry_policy(retry_policy())
+}
+
+fn waypoints() -> Result<(WayPoint, WayPoint)> {
+    Ok((
+        WayPoint::new_from_location(Location::new(37.419734, -122.08278, None), None)?,
+        WayPoint::new_from_location(Location::new(37.41767, -122.0796, None), None)?,
+    ))
+}
+
+fn service_account_credentials(handle: &FakeGoogleServerHandle) -> Result<ServiceAccountCredentials> {
+    let credentials = json!({
+        "type": "service_account",
+        "project_id": "test-project",
+        "private_key_id": "test-key-id",
+        "private_key": SERVICE_ACCOUNT_KEY,
+        "client_email": "test@test-project.iam.gserviceaccount.com",
+        "client_id": "1234567890",
+        "auth_uri": "https://accounts.google.com/o/oauth2/auth",
+        "token_uri": handle.token_uri(),
+        "auth_provider_x509_cert_url": "https://www.googleapis.com/oauth2/v1/certs",
+        "client_x509_cert_url": "https://www.googleapis.com/robot/v1/metadata/x509/test",
+        "universe_domain": "googleapis.com",
+    });
+    ServiceAccountCredentials::from_service_account_info(credentials.to_string())
+}
+
+fn service_error(error: &anyhow::Error) -> &ServiceError {
+    error.downcast_ref::<ServiceError>().expect("error is a ServiceError")
+}
+
+
+#[tokio::test]
+async fn translate() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+
+    let response = translate_service(&handle, "test-api-key").translate(vec!["hello"], "ja", None).await?;
+
+    let translation = &response.data.translations[0];
+    assert_eq!(translation.translated_text, "[ja] ĥéļļö");
+    assert_eq!(translation.detected_source_language.as_deref(), Some("en"));
+    assert_eq!(server.request_count(FakeEndpoint::Translate), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn detect_and_list_languages() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    let mut translate_service = translate_service(&handle, "test-api-key");
+
+    let detections = translate_service.detect_language(vec!["hello", "こんにちは"]).await?;
+    let languages: Vec<&str> = detections.data.detections.iter().map(|detection| detection[0].language.as_str()).collect();
+    assert_eq!(languages, ["en", "ja"]);
+
+    let languages = translate_service.list_languages(Some("en"), None).await?;
+    let japanese = languages.data.languages.iter().find(|language| language.language == "ja").expect("ja is listed");
+    assert_eq!(japanese.name.as_deref(), Some("Japanese"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_route_honors_the_field_mask() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    let (origin, destination) = waypoints()?;
+
+    let response = route_service(&handle).get_route(&origin, &destination, Some(vec!["routes.distanceMeters"]), None).await?;
+
+    assert_eq!(response.routes.len(), 1);
+    assert!(response.routes[0].distance_meters.is_some_and(|distance| distance > 0));
+    assert_eq!(response.routes[0].duration, None);
+    assert!(response.routes[0].legs.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_route_matrix() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    let (origin, destination) = waypoints()?;
+    let origins = vec![RouteMatrixOrigin::new(&origin), RouteMatrixOrigin::new(&destination)];
+    let destinations = vec![RouteMatrixOrigin::new(&origin), RouteMatrixOrigin::new(&destination)];
+
+    let elements = route_service(&handle).get_route_matrix(&origins, &destinations, None, None).await?;
+
+    let mut indexes: Vec<(i32, i32)> = elements.iter().map(|element| (element.origin_index, element.destination_index)).collect();
+    indexes.sort();
+    assert_eq!(indexes, [(0, 0), (0, 1), (1, 0), (1, 1)]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unavailable_is_retried() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    server.inject_error(FakeEndpoint::ComputeRoutes, InjectedError::new(503).with_times(2));
+    let (origin, destination) = waypoints()?;
+
+    let response = route_service(&handle).get_route(&origin, &destination, None, None).await?;
+
+    assert_eq!(response.routes.len(), 1);
+    assert_eq!(server.request_count(FakeEndpoint::ComputeRoutes), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalid_argument_is_not_retried() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    server.inject_error(FakeEndpoint::Translate, InjectedError::new(400).with_message("Invalid target language."));
+
+    let error = translate_service(&handle, "test-api-key").translate(vec!["hello"], "xx", None).await.unwrap_err();
+
+    let error = service_error(&error);
+    assert!(error.is_invalid_argument());
+    assert_eq!((error.http_status(), error.message()), (400, "Invalid target language."));
+    assert_eq!(server.request_count(FakeEndpoint::Translate), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn retries_are_limited_by_the_retry_policy() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    server.inject_error(FakeEndpoint::ComputeRoutes, InjectedError::new(503));
+    let (origin, destination) = waypoints()?;
+
+    let mut route_service = route_service(&handle).with_retry_policy(retry_policy().with_max_attempts(3));
+    let error = route_service.get_route(&origin, &destination, None, None).await.unwrap_err();
+
+    assert_eq!(service_error(&error).status(), Some("UNAVAILABLE"));
+    assert_eq!(server.request_count(FakeEndpoint::ComputeRoutes), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn retry_waits_for_the_retry_info_delay() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    let retry_info = json!({ "@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "0.2s" });
+    server.inject_error(FakeEndpoint::Translate, InjectedError::new(429).with_detail(retry_info).with_times(1));
+
+    let started = Instant::now();
+    translate_service(&handle, "test-api-key").translate(vec!["hello"], "ja", None).await?;
+
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert_eq!(server.request_count(FakeEndpoint::Translate), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn service_account_token_is_reused() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    let credentials = service_account_credentials(&handle)?;
+
+    let mut translate_service = TranslateService::new_with_credentials(credentials).with_endpoint(handle.url());
+    translate_service.translate(vec!["hello"], "ja", None).await?;
+    translate_service.translate(vec!["world"], "ja", None).await?;
+
+    assert_eq!(server.request_count(FakeEndpoint::Token), 1);
+    assert_eq!(server.request_count(FakeEndpoint::Translate), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn response_cache_is_shared_per_api_key() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    let response_cache = ResponseCache::in_memory(16);
+
+    let mut first = translate_service(&handle, "first-api-key").with_response_cache(response_cache.clone());
+    let mut second = translate_service(&handle, "second-api-key").with_response_cache(response_cache);
+    first.translate(vec!["hello"], "ja", None).await?;
+    let cached = first.translate(vec!["hello"], "ja", None).await?;
+    assert_eq!(cached.data.translations[0].translated_text, "[ja] ĥéļļö");
+    assert_eq!(server.request_count(FakeEndpoint::Translate), 1);
+
+    second.translate(vec!["hello"], "ja", None).await?;
+    assert_eq!(server.request_count(FakeEndpoint::Translate), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn retry_delay_longer_than_max_backoff_is_not_waited_for() -> Result<()> {
+    let server = FakeGoogleServer::new();
+    let handle = server.start()?;
+    let retry_info = json!({ "@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "86400s" });
+    server.inject_error(FakeEndpoint::Translate, InjectedError::new(429).with_detail(retry_info));
+
+    let started = Instant::now();
+    let mut translate_service = translate_service(&handle, "test-api-key").with_retry_policy(retry_policy().with_max_backoff(Duration::from_secs(10)));
+    let error = translate_service.translate(vec!["hello"], "ja", None).await.unwrap_err();
+
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(service_error(&error).status(), Some("RESOURCE_EXHAUSTED"));
+    assert_eq!(server.request_count(FakeEndpoint::Translate), 1);
+    Ok(())
+}
@@ -0,0 +1,285 @@
+#![cfg(feature = "grpc")]
+
+//! `RouteService` over gRPC against a local tonic server implementing `google.maps.routing.v2.Routes`.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::StreamExt;
+use google_api_rust_client_unoffical::services::request_options::{RequestError, RequestOptions};
+use google_api_rust_client_unoffical::services::retry_policy::RetryPolicy;
+use google_api_rust_client_unoffical::services::route_service::common_models::{Location, WayPoint};
+use google_api_rust_client_unoffical::services::route_service::get_route_matrix::request_model::RouteMatrixOrigin;
+use google_api_rust_client_unoffical::services::route_service::grpc::proto;
+use google_api_rust_client_unoffical::services::route_service::RouteService;
+use reqwest::Url;
+use serde_json::json;
+use tokio::net::TcpListener;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{empty_body, http, BoxFuture};
+use tonic::server::NamedService;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Body, Server};
+use tokio_util::sync::CancellationToken;
+use tonic::{Request, Response, Status};
+
+
+#[derive(Debug, Default)]
+struct Calls {
+    count: AtomicUsize,
+    // number of calls to fail with UNAVAILABLE before answering
+    unavailable: AtomicUsize,
+    field_masks: Mutex<Vec<String>>,
+    request_ids: Mutex<Vec<String>>,
+    requests: Mutex<Vec<proto::ComputeRoutesRequest>>,
+}
+
+impl Calls {
+    fn record<T>(&self, request: &Request<T>) -> Option<Status> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        let field_mask = request.metadata().get("x-goog-fieldmask").and_then(|value| value.to_str().ok()).unwrap_or_default();
+        self.field_masks.lock().unwrap().push(field_mask.to_owned());
+        let request_id = request.metadata().get("x-request-id").and_then(|value| value.to_str().ok()).unwrap_or_default();
+        self.request_ids.lock().unwrap().push(request_id.to_owned());
+        let unavailable = self.unavailable.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+        unavailable.ok().map(|_| Status::unavailable("try again"))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FakeRoutes {
+    calls: Arc<Calls>,
+}
+
+impl NamedService for FakeRoutes {
+    const NAME: &'static str = "google.maps.routing.v2.Routes";
+}
+
+impl tower::Service<http::Request<Body>> for FakeRoutes {
+    type Response = http::Response<tonic::body::BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let calls = self.calls.clone();
+        match request.uri().path() {
+            "/google.maps.routing.v2.Routes/ComputeRoutes" => Box::pin(async move {
+                let method = tower::service_fn(move |request: Request<proto::ComputeRoutesRequest>| {
+                    let calls = calls.clone();
+                    async move {
+                        if let Some(status) = calls.record(&request) {
+                            return Err(status);
+                        }
+                        calls.requests.lock().unwrap().push(request.get_ref().clone());
+                        Ok::<_, Status>(Response::new(proto::ComputeRoutesResponse { routes: vec![route()] }))
+                    }
+                });
+                let mut grpc = tonic::server::Grpc::new(ProstCodec::<proto::ComputeRoutesResponse, proto::ComputeRoutesRequest>::default());
+                Ok(grpc.unary(method, request).await)
+            }),
+            "/google.maps.routing.v2.Routes/ComputeRouteMatrix" => Box::pin(async move {
+                let method = tower::service_fn(move |request: Request<proto::ComputeRouteMatrixRequest>| {
+                    let calls = calls.clone();
+                    async move {
+                        if let Some(status) = calls.record(&request) {
+                            return Err(status);
+                        }
+                        let request = request.into_inner();
+                        let elements: Vec<proto::RouteMatrixElement> = (0..request.origins.len() as i32)
+                            .flat_map(|origin| (0..request.destinations.len() as i32).map(move |destination| element(origin, destination)))
+                            .collect();
+                        Ok(Response::new(futures::stream::iter(elements.into_iter().map(Ok::<_, Status>))))
+                    }
+                });
+                let mut grpc = tonic::server::Grpc::new(ProstCodec::<proto::RouteMatrixElement, proto::ComputeRouteMatrixRequest>::default());
+                Ok(grpc.server_streaming(method, request).await)
+            }),
+            _ => Box::pin(async move {
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .unwrap())
+            }),
+        }
+    }
+}
+
+fn route() -> proto::Route {
+    proto::Route {
+        distance_meters: 1200,
+        duration: Some(prost_types::Duration { seconds: 95, nanos: 500_000_000 }),
+        polyline: Some(proto::Polyline { polyline_type: Some(proto::polyline::PolylineType::EncodedPolyline("_p~iF~ps|U".to_owned())) }),
+        route_labels: vec![proto::RouteLabel::DefaultRoute as i32],
+        ..Default::default()
+    }
+}
+
+fn element(origin: i32, destination: i32) -> proto::RouteMatrixElement {
+    proto::RouteMatrixElement {
+        origin_index: Some(origin),
+        destination_index: Some(destination),
+        distance_meters: 100 * (origin + 1) + destination,
+        duration: Some(prost_types::Duration { seconds: 60, nanos: 0 }),
+        condition: proto::RouteMatrixElementCondition::RouteExists as i32,
+        ..Default::default()
+    }
+}
+
+async fn serve() -> Result<(Url, Arc<Calls>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = Url::parse(&format!("http://{}/", listener.local_addr()?))?;
+    let incoming = TcpIncoming::from_listener(listener, true, None).map_err(|error| anyhow::anyhow!(error))?;
+
+    let calls = Arc::new(Calls::default());
+    let routes = FakeRoutes { calls: calls.clone() };
+    tokio::spawn(Server::builder().add_service(routes).serve_with_incoming(incoming));
+    Ok((url, calls))
+}
+
+fn route_service(url: &Url) -> RouteService {
+    RouteService::new_with_api_key("test-api-key".to_owned())
+        .with_grpc()
+        .with_endpoint(url.clone())
+        .with_retry_policy(RetryPolicy::new().with_initial_backoff(Duration::from_millis(1)).with_jitter(false))
+}
+
+fn waypoints() -> Result<(WayPoint, WayPoint)> {
+    Ok((
+        WayPoint::new_from_location(Location::new(37.419734, -122.08278, None), None)?,
+        WayPoint::new_from_location(Location::new(37.41767, -122.0796, None), None)?,
+    ))
+}
+
+
+#[tokio::test]
+async fn get_route_over_grpc() -> Result<()> {
+    let (url, calls) = serve().await?;
+    let (origin, destination) = waypoints()?;
+
+    let response = route_service(&url).get_route(&origin, &destination, Some(vec!["routes.duration", "routes.distanceMeters"]), None).await?;
+
+    assert_eq!(response.routes.len(), 1);
+    assert_eq!(response.routes[0].distance_meters, Some(1200));
+    assert_eq!(response.routes[0].duration.as_deref(), Some("95.5s"));
+    assert_eq!(calls.field_masks.lock().unwrap().as_slice(), ["routes.duration,routes.distance_meters"]);
+
+    let requests = calls.requests.lock().unwrap();
+    let lat_lng = match &requests[0].origin.as_ref().unwrap().location_type {
+        Some(proto::waypoint::LocationType::Location(location)) => location.lat_lng.clone().unwrap(),
+        other => panic!("unexpected origin {:?}", other),
+    };
+    // `Location` holds f32 coordinates
+    assert!((lat_lng.latitude - 37.419734).abs() < 1e-5 && (lat_lng.longitude + 122.08278).abs() < 1e-5);
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_route_matrix_stream_over_grpc() -> Result<()> {
+    let (url, _calls) = serve().await?;
+    let (origin, destination) = waypoints()?;
+    let origins = vec![RouteMatrixOrigin::new(&origin), RouteMatrixOrigin::new(&destination)];
+    let destinations = vec![RouteMatrixOrigin::new(&destination)];
+
+    let mut route_service = route_service(&url);
+    let elements: Vec<_> = route_service.get_route_matrix_stream(&origins, &destinations, None, None).await?.collect().await;
+    let elements = elements.into_iter().collect::<Result<Vec<_>>>()?;
+
+    assert_eq!(elements.len(), 2);
+    assert_eq!((elements[1].origin_index, elements[1].distance_meters), (1, Some(200)));
+    assert_eq!(elements[1].duration.as_deref(), Some("60s"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_route_matrix_stream_with_options() -> Result<()> {
+    let (url, calls) = serve().await?;
+    let (origin, destination) = waypoints()?;
+    let origins = vec![RouteMatrixOrigin::new(&origin)];
+    let destinations = vec![RouteMatrixOrigin::new(&origin), RouteMatrixOrigin::new(&destination)];
+    let mut route_service = route_service(&url);
+
+    let options = RequestOptions::new().with_timeout(Duration::from_secs(10)).with_request_id("matrix-1");
+    let elements: Vec<_> = route_service.get_route_matrix_stream_with_options(&origins, &destinations, Some(vec!["duration"]), None, &options).await?.collect().await;
+    assert_eq!(elements.into_iter().collect::<Result<Vec<_>>>()?.len(), 2);
+    assert_eq!(calls.request_ids.lock().unwrap().as_slice(), ["matrix-1"]);
+    assert_eq!(calls.field_masks.lock().unwrap().as_slice(), ["duration"]);
+
+    let cancellation_token = CancellationToken::new();
+    let options = RequestOptions::new().with_cancellation_token(cancellation_token.clone());
+    let elements = route_service.get_route_matrix_stream_with_options(&origins, &destinations, None, None, &options).await?;
+    cancellation_token.cancel();
+    let elements: Vec<_> = elements.collect().await;
+    assert_eq!(elements.len(), 1);
+    assert_eq!(elements[0].as_ref().unwrap_err().downcast_ref::<RequestError>(), Some(&RequestError::Cancelled));
+
+    let result = route_service.get_route_matrix_stream_with_options(&origins, &destinations, None, None, &options).await;
+    assert_eq!(result.err().and_then(|error| error.downcast::<RequestError>().ok()), Some(RequestError::Cancelled));
+    assert_eq!(calls.count.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn params_and_paths_the_messages_cannot_carry_are_rejected() -> Result<()> {
+    let (url, calls) = serve().await?;
+    let (origin, destination) = waypoints()?;
+    let mut route_service = route_service(&url);
+
+    let params = HashMap::from([("trafficModel".to_owned(), json!("PESSIMISTIC"))]);
+    assert!(route_service.get_route(&origin, &destination, None, Some(params)).await.is_err());
+    assert!(route_service.get_route(&origin, &destination, Some(vec!["routes.travelAdvisory"]), None).await.is_err());
+    assert_eq!(calls.count.load(Ordering::SeqCst), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unavailable_is_retried() -> Result<()> {
+    let (url, calls) = serve().await?;
+    calls.unavailable.store(2, Ordering::SeqCst);
+    let (origin, destination) = waypoints()?;
+
+    let response = route_service(&url).get_route(&origin, &destination, None, None).await?;
+
+    assert_eq!(response.routes.len(), 1);
+    assert_eq!(calls.count.load(Ordering::SeqCst), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn retries_are_limited_by_the_retry_policy() -> Result<()> {
+    let (url, calls) = serve().await?;
+    calls.unavailable.store(5, Ordering::SeqCst);
+    let (origin, destination) = waypoints()?;
+
+    let result = route_service(&url).with_retry_policy(RetryPolicy::none()).get_route(&origin, &destination, None, None).await;
+
+    assert!(result.is_err());
+    assert_eq!(calls.count.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn with_endpoint_reconnects_the_channel() -> Result<()> {
+    let (first_url, first_calls) = serve().await?;
+    let (second_url, second_calls) = serve().await?;
+    let (origin, destination) = waypoints()?;
+
+    let mut route_service = route_service(&first_url);
+    route_service.get_route(&origin, &destination, None, None).await?;
+    let mut route_service = route_service.with_endpoint(second_url);
+    route_service.get_route(&origin, &destination, None, None).await?;
+
+    assert_eq!(first_calls.count.load(Ordering::SeqCst), 1);
+    assert_eq!(second_calls.count.load(Ordering::SeqCst), 1);
+    Ok(())
+}